@@ -0,0 +1,140 @@
+use serde_json::{Map, Number, Value};
+use swc_core::{
+    common::{errors::HANDLER, Span},
+    ecma::ast::*,
+    ecma::atoms::JsWord,
+};
+
+use crate::route_segment_config::unwrap_expr;
+
+/// Exports of `metadata` and `generateMetadata`, with the spans of their names.
+pub(crate) fn find_metadata_exports(module: &Module) -> Vec<(JsWord, Span)> {
+    let mut exports = vec![];
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => match decl {
+                Decl::Fn(FnDecl { ident, .. }) => exports.push((ident.sym.clone(), ident.span)),
+                Decl::Var(var) => {
+                    for decl in &var.decls {
+                        if let Pat::Ident(BindingIdent { id, .. }) = &decl.name {
+                            exports.push((id.sym.clone(), id.span));
+                        }
+                    }
+                }
+                _ => {}
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src: None,
+                ..
+            })) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        span,
+                        orig,
+                        exported,
+                        ..
+                    }) = specifier
+                    {
+                        let name = match exported.as_ref().unwrap_or(orig) {
+                            ModuleExportName::Ident(ident) => ident.sym.clone(),
+                            ModuleExportName::Str(s) => s.value.clone(),
+                        };
+                        exports.push((name, *span));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    exports.retain(|(name, _)| &**name == "metadata" || &**name == "generateMetadata");
+    exports
+}
+
+/// Checks the metadata exports of a layout or page which is a Server
+/// Component, and returns the `metadata` object if it is static.
+pub(crate) fn collect_static_metadata(module: &Module) -> Option<Value> {
+    let exports = find_metadata_exports(module);
+    let has_metadata = exports.iter().any(|(name, _)| &**name == "metadata");
+    let generate_metadata = exports
+        .iter()
+        .find(|(name, _)| &**name == "generateMetadata");
+    if let (true, Some((_, span))) = (has_metadata, generate_metadata) {
+        HANDLER.with(|handler| {
+            handler
+                .struct_span_err(
+                    *span,
+                    "`metadata` and `generateMetadata` can't be exported from the same layout or \
+                     page. Export `generateMetadata` only when the metadata depends on dynamic \
+                     data.",
+                )
+                .emit()
+        })
+    }
+    if !has_metadata {
+        return None;
+    }
+
+    module.body.iter().find_map(|item| match item {
+        ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+            decl: Decl::Var(var),
+            ..
+        })) => var
+            .decls
+            .iter()
+            .find_map(|decl| match (&decl.name, &decl.init) {
+                (Pat::Ident(BindingIdent { id, .. }), Some(init)) if &*id.sym == "metadata" => {
+                    match unwrap_expr(init) {
+                        expr @ Expr::Object(..) => to_json(expr),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }),
+        _ => None,
+    })
+}
+
+/// The JSON value of an expression made of literals only.
+fn to_json(expr: &Expr) -> Option<Value> {
+    Some(match unwrap_expr(expr) {
+        Expr::Lit(Lit::Str(s)) => Value::from(&*s.value),
+        Expr::Lit(Lit::Num(n)) if n.value.fract() == 0.0 => Value::from(n.value as i64),
+        Expr::Lit(Lit::Num(n)) => Value::Number(Number::from_f64(n.value)?),
+        Expr::Lit(Lit::Bool(b)) => Value::Bool(b.value),
+        Expr::Lit(Lit::Null(..)) => Value::Null,
+        Expr::Tpl(Tpl { exprs, quasis, .. }) if exprs.is_empty() => {
+            Value::from(&**quasis.first()?.cooked.as_ref()?)
+        }
+        Expr::Array(ArrayLit { elems, .. }) => Value::Array(
+            elems
+                .iter()
+                .map(|elem| match elem {
+                    Some(ExprOrSpread { spread: None, expr }) => to_json(expr),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+        ),
+        Expr::Object(ObjectLit { props, .. }) => {
+            let mut object = Map::new();
+            for prop in props {
+                let (key, value) = match prop {
+                    PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { key, value })) => {
+                        (key, value)
+                    }
+                    _ => return None,
+                };
+                let key = match key {
+                    PropName::Ident(ident) => ident.sym.to_string(),
+                    PropName::Str(s) => s.value.to_string(),
+                    PropName::Num(n) => n.value.to_string(),
+                    _ => return None,
+                };
+                object.insert(key, to_json(value)?);
+            }
+            Value::Object(object)
+        }
+        _ => return None,
+    })
+}
@@ -0,0 +1,240 @@
+use fxhash::FxHashMap;
+use swc_core::{
+    common::{errors::HANDLER, Span, Spanned},
+    ecma::ast::*,
+    ecma::visit::{noop_visit_type, Visit, VisitWith},
+};
+
+/// Reports async components of a "use client" module, which React can only
+/// render on the server. Exported functions are components when their name
+/// starts with an uppercase letter, or when they are an anonymous default
+/// export returning JSX.
+pub(crate) fn assert_no_async_client_components(module: &Module) {
+    // Top level async functions named like components, by binding.
+    let mut async_components: FxHashMap<Id, Span> = Default::default();
+    for item in &module.body {
+        match item {
+            ModuleItem::Stmt(Stmt::Decl(decl))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => match decl
+            {
+                Decl::Fn(FnDecl { ident, function }) if function.is_async => {
+                    if is_component_name(&ident.sym) {
+                        async_components.insert(ident.to_id(), ident.span);
+                    }
+                }
+                Decl::Var(var) => {
+                    for decl in &var.decls {
+                        if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) =
+                            (&decl.name, &decl.init)
+                        {
+                            if is_component_name(&id.sym) && is_async_function(init) {
+                                async_components.insert(id.to_id(), id.span);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(FnDecl { ident, .. }),
+                ..
+            })) if async_components.contains_key(&ident.to_id()) => {
+                report(ident.span, &ident.sym);
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            })) => {
+                for decl in &var.decls {
+                    if let Pat::Ident(BindingIdent { id, .. }) = &decl.name {
+                        if async_components.contains_key(&id.to_id()) {
+                            report(id.span, &id.sym);
+                        }
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::Fn(FnExpr { ident, function }),
+                ..
+            })) if is_async_default_component(ident.as_ref(), function) => match ident {
+                Some(ident) => report(ident.span, &ident.sym),
+                None => report(function.span, "default"),
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(ExportDefaultExpr {
+                expr,
+                ..
+            })) => match unwrap_parens(expr) {
+                Expr::Ident(ident) => {
+                    if let Some(span) = async_components.get(&ident.to_id()) {
+                        report(*span, &ident.sym);
+                    }
+                }
+                Expr::Fn(FnExpr { ident, function })
+                    if is_async_default_component(ident.as_ref(), function) =>
+                {
+                    match ident {
+                        Some(ident) => report(ident.span, &ident.sym),
+                        None => report(expr.span(), "default"),
+                    }
+                }
+                Expr::Arrow(ArrowExpr {
+                    is_async: true,
+                    body,
+                    ..
+                }) if returns_jsx(body) => report(expr.span(), "default"),
+                _ => {}
+            },
+            ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src: None,
+                ..
+            })) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        orig: ModuleExportName::Ident(orig),
+                        ..
+                    }) = specifier
+                    {
+                        if let Some(span) = async_components.get(&orig.to_id()) {
+                            report(*span, &orig.sym);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !async_components.is_empty() {
+        module.visit_with(&mut RenderedAsyncComponents {
+            async_components: &async_components,
+        });
+    }
+}
+
+/// `<Profile />`, where `Profile` is an async function of the module.
+struct RenderedAsyncComponents<'a> {
+    async_components: &'a FxHashMap<Id, Span>,
+}
+
+impl Visit for RenderedAsyncComponents<'_> {
+    noop_visit_type!();
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        if let JSXElementName::Ident(ident) = &element.name {
+            if self.async_components.contains_key(&ident.to_id()) {
+                report(ident.span, &ident.sym);
+            }
+        }
+        element.visit_children_with(self);
+    }
+}
+
+/// Finds `return <div />` in a function body, but not in the functions nested
+/// in it.
+#[derive(Default)]
+struct ReturnedJsx {
+    found: bool,
+}
+
+impl Visit for ReturnedJsx {
+    noop_visit_type!();
+
+    fn visit_return_stmt(&mut self, stmt: &ReturnStmt) {
+        if let Some(arg) = &stmt.arg {
+            self.found |= is_jsx(arg);
+        }
+    }
+
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
+
+fn is_component_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+}
+
+/// Named default exports are components like other exports. Anonymous ones,
+/// like `export default async function () {}`, only when they return JSX.
+fn is_async_default_component(ident: Option<&Ident>, function: &Function) -> bool {
+    if !function.is_async {
+        return false;
+    }
+    match ident {
+        Some(ident) => is_component_name(&ident.sym),
+        None => function.body.as_ref().map_or(false, block_returns_jsx),
+    }
+}
+
+fn returns_jsx(body: &BlockStmtOrExpr) -> bool {
+    match body {
+        BlockStmtOrExpr::BlockStmt(block) => block_returns_jsx(block),
+        BlockStmtOrExpr::Expr(expr) => is_jsx(expr),
+    }
+}
+
+fn block_returns_jsx(block: &BlockStmt) -> bool {
+    let mut returned = ReturnedJsx::default();
+    block.visit_with(&mut returned);
+    returned.found
+}
+
+/// `<div />`, `cond ? <div /> : null` or `cond && <div />`.
+fn is_jsx(expr: &Expr) -> bool {
+    match unwrap_parens(expr) {
+        Expr::JSXElement(..) | Expr::JSXFragment(..) => true,
+        Expr::Cond(CondExpr { cons, alt, .. }) => is_jsx(cons) || is_jsx(alt),
+        Expr::Bin(BinExpr {
+            op: op!("&&") | op!("||") | op!("??"),
+            left,
+            right,
+            ..
+        }) => is_jsx(left) || is_jsx(right),
+        _ => false,
+    }
+}
+
+fn is_async_function(expr: &Expr) -> bool {
+    match unwrap_parens(expr) {
+        Expr::Fn(FnExpr { function, .. }) => function.is_async,
+        Expr::Arrow(ArrowExpr { is_async, .. }) => *is_async,
+        _ => false,
+    }
+}
+
+fn unwrap_parens(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. }) => unwrap_parens(expr),
+        _ => expr,
+    }
+}
+
+fn report(span: Span, name: &str) {
+    let component = if name == "default" {
+        "The default export".to_string()
+    } else {
+        format!("`{}`", name)
+    };
+    HANDLER.with(|handler| {
+        handler
+            .struct_span_err(
+                span,
+                &format!(
+                    "{} is an async Client Component. Only Server Components can be async. Remove \
+                     `async` from the component, or remove the \"use client\" directive at the \
+                     top of the file.",
+                    component
+                ),
+            )
+            .emit()
+    })
+}
@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use swc_core::common::{
+    errors::{
+        Diagnostic as SwcDiagnostic, DiagnosticBuilder, DiagnosticId, Emitter, Handler, Level,
+        HANDLER,
+    },
+    SourceMap, Span,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Help,
+}
+
+/// 1-based line and 0-based column, matching the convention used by source
+/// maps and most JS tooling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Stable identifier of the diagnostic, e.g. `NEXT_RSC_ERR_REACT_API`.
+    pub code: Option<String>,
+    pub filename: Option<String>,
+    pub start: Option<Location>,
+    pub end: Option<Location>,
+}
+
+impl Diagnostic {
+    /// A diagnostic for failures which were not reported through `HANDLER`,
+    /// like invalid options or unreadable files.
+    pub fn from_error_message(message: String) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            code: code_from_message(&message),
+            message,
+            filename: None,
+            start: None,
+            end: None,
+        }
+    }
+
+    fn from_swc(cm: &SourceMap, diagnostic: &SwcDiagnostic) -> Option<Self> {
+        let severity = match diagnostic.level {
+            Level::Bug | Level::Fatal | Level::PhaseFatal | Level::Error => Severity::Error,
+            Level::Warning => Severity::Warning,
+            Level::Note => Severity::Note,
+            Level::Help => Severity::Help,
+            Level::Cancelled | Level::FailureNote => return None,
+        };
+        let message = diagnostic.message();
+        let code = match &diagnostic.code {
+            Some(DiagnosticId::Error(code)) | Some(DiagnosticId::Lint(code)) => Some(code.clone()),
+            None => code_from_message(&message),
+        };
+
+        let mut result = Diagnostic {
+            severity,
+            message,
+            code,
+            filename: None,
+            start: None,
+            end: None,
+        };
+        if let Some(span) = diagnostic.span.primary_span() {
+            result.locate(cm, span);
+        }
+        Some(result)
+    }
+
+    fn locate(&mut self, cm: &SourceMap, span: Span) {
+        if span.is_dummy() {
+            return;
+        }
+        let start = cm.lookup_char_pos(span.lo);
+        let end = cm.lookup_char_pos(span.hi);
+
+        self.filename = Some(start.file.name.to_string());
+        self.start = Some(Location {
+            line: start.line,
+            column: start.col.0,
+        });
+        self.end = Some(Location {
+            line: end.line,
+            column: end.col.0,
+        });
+    }
+}
+
+/// Existing passes prefix their messages with a stable code, like
+/// `NEXT_RSC_ERR_SERVER_IMPORT: client-only`.
+fn code_from_message(message: &str) -> Option<String> {
+    let (prefix, _) = message.split_once(':')?;
+    let is_code = prefix.starts_with("NEXT_")
+        && prefix
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+
+    if is_code {
+        Some(prefix.to_string())
+    } else {
+        None
+    }
+}
+
+pub fn has_errors(diagnostics: &[Diagnostic]) -> bool {
+    diagnostics.iter().any(|d| d.severity == Severity::Error)
+}
+
+#[derive(Clone, Default)]
+struct Collector {
+    emitted: Arc<Mutex<Vec<SwcDiagnostic>>>,
+}
+
+impl Emitter for Collector {
+    fn emit(&mut self, db: &DiagnosticBuilder<'_>) {
+        self.emitted.lock().unwrap().push((**db).clone());
+    }
+}
+
+/// Runs `op` with a handler which records every emitted diagnostic, then
+/// forwards them to `handler` so the rendered error output stays the same.
+pub fn with_diagnostics<F, Ret>(
+    cm: Arc<SourceMap>,
+    handler: &Handler,
+    op: F,
+) -> (Ret, Vec<Diagnostic>)
+where
+    F: FnOnce(&Handler) -> Ret,
+{
+    let collector = Collector::default();
+    let emitted = collector.emitted.clone();
+    let collecting_handler = Handler::with_emitter(true, false, Box::new(collector));
+
+    let ret = HANDLER.set(&collecting_handler, || op(&collecting_handler));
+
+    let emitted = std::mem::take(&mut *emitted.lock().unwrap());
+    let diagnostics = emitted
+        .iter()
+        .filter_map(|d| Diagnostic::from_swc(&cm, d))
+        .collect();
+    for d in emitted {
+        DiagnosticBuilder::new_diagnostic(handler, d).emit();
+    }
+
+    (ret, diagnostics)
+}
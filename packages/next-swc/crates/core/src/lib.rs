@@ -30,42 +30,61 @@ DEALINGS IN THE SOFTWARE.
 #![deny(clippy::all)]
 #![feature(box_patterns)]
 
-use auto_cjs::contains_cjs;
 use either::Either;
 use fxhash::FxHashSet;
+use metadata::ModuleMetadata;
+use module_format::ModuleFormat;
+use pass_stats::{PassStats, PassTracer};
+use schemars::JsonSchema;
 use serde::Deserialize;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::{path::PathBuf, sync::Arc};
 
 use swc_core::{
     base::config::ModuleConfig,
-    common::{chain, comments::Comments, pass::Optional, FileName, SourceFile, SourceMap},
-    ecma::ast::EsVersion,
+    common::{chain, comments::Comments, pass::Optional, FileName, Mark, SourceFile, SourceMap},
+    ecma::ast::{EsVersion, Program},
     ecma::parser::parse_file_as_module,
-    ecma::transforms::base::pass::noop,
-    ecma::visit::Fold,
+    ecma::transforms::base::{pass::noop, resolver},
+    ecma::visit::{Fold, FoldWith},
 };
 
 pub mod amp_attributes;
-mod auto_cjs;
+mod app_metadata;
+mod async_client_components;
+pub mod diagnostics;
 pub mod disallow_re_export_all_in_page;
+pub mod manifests;
+pub mod metadata;
+pub mod module_format;
 pub mod next_dynamic;
 pub mod next_font_loaders;
 pub mod next_ssg;
+pub mod options_schema;
 pub mod page_config;
+pub mod pass_stats;
+pub mod plugins;
 pub mod react_remove_properties;
 pub mod react_server_components;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod relay;
 pub mod remove_console;
+mod route_segment_config;
+mod serializable_props;
+pub mod server_actions;
+pub mod server_components_graph;
 pub mod shake_exports;
 mod top_level_binding_collector;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transform_cache;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct TransformOptions {
+    /// Validated by swc itself, and listed by `options_schema::SWC_OPTIONS`.
     #[serde(flatten)]
+    #[schemars(skip)]
     pub swc: swc_core::base::config::Options,
 
     #[serde(default)]
@@ -93,6 +112,7 @@ pub struct TransformOptions {
     pub styled_jsx: bool,
 
     #[serde(default)]
+    #[schemars(with = "Option<options_schema::StyledComponentsConfig>")]
     pub styled_components: Option<styled_components::Config>,
 
     #[serde(default)]
@@ -115,25 +135,79 @@ pub struct TransformOptions {
     pub shake_exports: Option<shake_exports::Config>,
 
     #[serde(default)]
+    #[schemars(with = "Option<options_schema::EmotionOptions>")]
     pub emotion: Option<swc_emotion::EmotionOptions>,
 
     #[serde(default)]
+    #[schemars(with = "Option<options_schema::ModularizeImportsConfig>")]
     pub modularize_imports: Option<modularize_imports::Config>,
 
     #[serde(default)]
     pub font_loaders: Option<next_font_loaders::Config>,
+
+    #[serde(default)]
+    pub next_dynamic: Option<next_dynamic::Config>,
+
+    /// The bundler the output is built with, see `next_dynamic::Bundler`.
+    #[serde(default)]
+    pub bundler: next_dynamic::Bundler,
+
+    /// Records the wall time and node counts of every pass into `pass_stats`.
+    #[serde(default)]
+    pub collect_pass_stats: bool,
+
+    /// Records the facts described by `ModuleMetadata` into `metadata`.
+    #[serde(default)]
+    pub collect_metadata: bool,
+
+    /// Rejects unknown fields, see `TransformOptions::from_json`.
+    #[serde(default)]
+    pub strict_options: bool,
+
+    /// swc wasm plugins, which run after the other passes of
+    /// `custom_before_pass`. Only the native bindings can run them, the wasm
+    /// bindings reject this option.
+    #[serde(default)]
+    pub plugins: Vec<plugins::PluginConfig>,
+}
+
+/// State shared between the caller and the passes of `custom_before_pass`,
+/// which report into it. The caller keeps a clone to read the outputs once
+/// the transform is done.
+#[derive(Clone, Default)]
+pub struct PassContext {
+    /// Packages whose imports were removed by `next_ssg`.
+    pub eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+    /// Only filled if `collectPassStats` is set.
+    pub pass_stats: Rc<RefCell<Vec<PassStats>>>,
+    /// Only filled if `collectMetadata` is set.
+    pub metadata: Rc<RefCell<ModuleMetadata>>,
+    /// Only missing for programs which were not parsed.
+    pub module_format: Rc<Cell<Option<ModuleFormat>>>,
 }
 
+/// `unresolved_mark` has to be the mark swc's resolver uses for the program,
+/// which callers pass to swc as `opts.swc.unresolved_mark`.
 pub fn custom_before_pass<'a, C: Comments + 'a>(
     cm: Arc<SourceMap>,
     file: Arc<SourceFile>,
     opts: &'a TransformOptions,
     comments: C,
-    eliminated_packages: Rc<RefCell<FxHashSet<String>>>,
+    context: PassContext,
+    unresolved_mark: Mark,
 ) -> impl Fold + 'a
 where
     C: Clone,
 {
+    let PassContext {
+        eliminated_packages,
+        pass_stats,
+        metadata,
+        module_format,
+    } = context;
+    let tracer = PassTracer::new(&file.name, opts.collect_pass_stats.then_some(pass_stats));
+    let metadata = opts.collect_metadata.then_some(metadata);
+
     #[cfg(target_arch = "wasm32")]
     let relay_plugin = noop();
 
@@ -151,106 +225,196 @@ where
     };
 
     chain!(
-        disallow_re_export_all_in_page::disallow_re_export_all_in_page(opts.is_page_file),
-        match &opts.server_components {
-            Some(config) if config.truthy() =>
-                Either::Left(react_server_components::server_components(
+        tracer.wrap(
+            "metadata",
+            match &metadata {
+                Some(metadata) => Either::Left(metadata::collect_metadata(metadata.clone())),
+                None => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "module_format",
+            module_format::module_format(unresolved_mark, module_format)
+        ),
+        tracer.wrap(
+            "disallow_re_export_all_in_page",
+            disallow_re_export_all_in_page::disallow_re_export_all_in_page(opts.is_page_file)
+        ),
+        tracer.wrap(
+            "react_server_components",
+            match &opts.server_components {
+                Some(config) if config.truthy() =>
+                    Either::Left(react_server_components::server_components(
+                        file.name.clone(),
+                        config.clone().with_esm_client_references(matches!(
+                            opts.swc.config.module,
+                            Some(ModuleConfig::Es6)
+                        )),
+                        comments.clone(),
+                        metadata.clone(),
+                    )),
+                _ => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "styled_jsx",
+            if opts.styled_jsx {
+                Either::Left(styled_jsx::visitor::styled_jsx(
+                    cm.clone(),
                     file.name.clone(),
+                ))
+            } else {
+                Either::Right(noop())
+            }
+        ),
+        tracer.wrap(
+            "styled_components",
+            match &opts.styled_components {
+                Some(config) => Either::Left(styled_components::styled_components(
+                    file.name.clone(),
+                    file.src_hash,
                     config.clone(),
-                    comments.clone(),
                 )),
-            _ => Either::Right(noop()),
-        },
-        if opts.styled_jsx {
-            Either::Left(styled_jsx::visitor::styled_jsx(
+                None => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "next_ssg",
+            Optional::new(
+                next_ssg::next_ssg(eliminated_packages),
+                !opts.disable_next_ssg
+            )
+        ),
+        tracer.wrap("amp_attributes", amp_attributes::amp_attributes()),
+        tracer.wrap(
+            "next_dynamic",
+            next_dynamic::next_dynamic(
                 cm.clone(),
+                opts.is_development,
+                opts.is_server,
+                opts.server_components.is_some(),
                 file.name.clone(),
-            ))
-        } else {
-            Either::Right(noop())
-        },
-        match &opts.styled_components {
-            Some(config) => Either::Left(styled_components::styled_components(
-                file.name.clone(),
-                file.src_hash,
-                config.clone(),
-            )),
-            None => Either::Right(noop()),
-        },
-        Optional::new(
-            next_ssg::next_ssg(eliminated_packages),
-            !opts.disable_next_ssg
+                opts.pages_dir.clone(),
+                next_dynamic::Config {
+                    bundler: opts.bundler.clone(),
+                    ..opts.next_dynamic.clone().unwrap_or_default()
+                },
+                metadata.clone()
+            )
         ),
-        amp_attributes::amp_attributes(),
-        next_dynamic::next_dynamic(
-            opts.is_development,
-            opts.is_server,
-            opts.server_components.is_some(),
-            file.name.clone(),
-            opts.pages_dir.clone()
+        tracer.wrap(
+            "page_config",
+            Optional::new(
+                page_config::page_config(opts.is_development, opts.is_page_file),
+                !opts.disable_page_config
+            )
         ),
-        Optional::new(
-            page_config::page_config(opts.is_development, opts.is_page_file),
-            !opts.disable_page_config
+        tracer.wrap("relay", relay_plugin),
+        tracer.wrap(
+            "remove_console",
+            match &opts.remove_console {
+                Some(config) if config.truthy() =>
+                    Either::Left(remove_console::remove_console(config.clone())),
+                _ => Either::Right(noop()),
+            }
         ),
-        relay_plugin,
-        match &opts.remove_console {
-            Some(config) if config.truthy() =>
-                Either::Left(remove_console::remove_console(config.clone())),
-            _ => Either::Right(noop()),
-        },
-        match &opts.react_remove_properties {
-            Some(config) if config.truthy() =>
-                Either::Left(react_remove_properties::remove_properties(config.clone())),
-            _ => Either::Right(noop()),
-        },
-        match &opts.shake_exports {
-            Some(config) => Either::Left(shake_exports::shake_exports(config.clone())),
-            None => Either::Right(noop()),
-        },
-        opts.emotion
-            .as_ref()
-            .and_then(|config| {
-                if !config.enabled.unwrap_or(false) {
-                    return None;
-                }
-                if let FileName::Real(path) = &file.name {
-                    path.to_str().map(|_| {
-                        Either::Left(swc_emotion::EmotionTransformer::new(
-                            config.clone(),
-                            path,
-                            cm,
-                            comments,
-                        ))
-                    })
+        tracer.wrap(
+            "react_remove_properties",
+            match &opts.react_remove_properties {
+                Some(config) if config.truthy() =>
+                    Either::Left(react_remove_properties::remove_properties(config.clone())),
+                _ => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "shake_exports",
+            match &opts.shake_exports {
+                Some(config) => Either::Left(shake_exports::shake_exports(config.clone())),
+                None => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "emotion",
+            opts.emotion
+                .as_ref()
+                .and_then(|config| {
+                    if !config.enabled.unwrap_or(false) {
+                        return None;
+                    }
+                    if let FileName::Real(path) = &file.name {
+                        path.to_str().map(|_| {
+                            Either::Left(swc_emotion::EmotionTransformer::new(
+                                config.clone(),
+                                path,
+                                cm.clone(),
+                                comments,
+                            ))
+                        })
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_else(|| Either::Right(noop()))
+        ),
+        tracer.wrap(
+            "modularize_imports",
+            match &opts.modularize_imports {
+                Some(config) =>
+                    Either::Left(modularize_imports::modularize_imports(config.clone())),
+                None => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "font_loaders",
+            match &opts.font_loaders {
+                Some(config) => Either::Left(next_font_loaders::next_font_loaders(
+                    config.clone(),
+                    metadata
+                )),
+                None => Either::Right(noop()),
+            }
+        ),
+        tracer.wrap(
+            "plugins",
+            plugins::plugins(
+                cm,
+                file.name.clone(),
+                if opts.is_development {
+                    "development"
                 } else {
-                    None
-                }
-            })
-            .unwrap_or_else(|| Either::Right(noop())),
-        match &opts.modularize_imports {
-            Some(config) => Either::Left(modularize_imports::modularize_imports(config.clone())),
-            None => Either::Right(noop()),
-        },
-        match &opts.font_loaders {
-            Some(config) => Either::Left(next_font_loaders::next_font_loaders(config.clone())),
-            None => Either::Right(noop()),
-        },
+                    "production"
+                },
+                opts.plugins.clone(),
+                unresolved_mark
+            )
+        ),
     )
 }
 
 impl TransformOptions {
+    /// Has to be called with swc's `GLOBALS` set, as it resolves the module to
+    /// detect its format.
     pub fn patch(mut self, fm: &SourceFile) -> Self {
         self.swc.swcrc = false;
 
+        // Every module with CommonJS exports mentions `exports` or
+        // `__esModule`, so the others are not parsed twice.
         let should_enable_commonjs = self.swc.config.module.is_none()
-            && (fm.src.contains("module.exports") || fm.src.contains("__esModule"))
+            && (fm.src.contains("exports") || fm.src.contains("__esModule"))
             && {
                 let syntax = self.swc.config.jsc.syntax.unwrap_or_default();
                 let target = self.swc.config.jsc.target.unwrap_or_else(EsVersion::latest);
 
                 parse_file_as_module(fm, syntax, target, None, &mut vec![])
-                    .map(|m| contains_cjs(&m))
+                    .map(|m| {
+                        let unresolved_mark = Mark::new();
+                        let program = Program::Module(m).fold_with(&mut resolver(
+                            unresolved_mark,
+                            Mark::new(),
+                            syntax.typescript(),
+                        ));
+                        module_format::needs_commonjs(&program, unresolved_mark)
+                    })
                     .unwrap_or_default()
             };
 
@@ -0,0 +1,228 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    ecma::ast::*,
+    ecma::visit::{noop_visit_type, Visit, VisitWith},
+};
+
+use crate::{diagnostics::Location, serializable_props::normalize};
+
+/// The part of the client reference manifest contributed by a "use client"
+/// module, recorded when `react_server_components` replaces it with
+/// references.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientReferenceFragment {
+    /// The path the references are created with.
+    pub module_path: String,
+    /// Referenced exports. Modules which are referenced as a whole, because
+    /// their exports are only known at runtime, only have `*`.
+    pub exports: Vec<String>,
+    pub chunking: ChunkingHints,
+}
+
+/// What the bundler has to know about a client entry to put it into chunks.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkingHints {
+    /// The module uses top level `await`, so loading it returns a promise.
+    pub is_async: bool,
+    /// Stylesheets imported by the module, which have to be loaded with it.
+    /// Relative imports are resolved against the path of the module, imports
+    /// of packages are left for the bundler to resolve.
+    pub css_imports: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientReferenceManifest {
+    /// Keyed by module path and then by export, like the manifest of the
+    /// flight plugin.
+    pub client_modules: BTreeMap<String, BTreeMap<String, ClientReference>>,
+    /// Stylesheets of the client modules, keyed by module path.
+    pub css_files: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientReference {
+    pub id: String,
+    pub name: String,
+    /// Filled in by the bundler once the chunks are known.
+    pub chunks: Vec<String>,
+    pub r#async: bool,
+}
+
+/// Merges the fragments of all the client entries of a build. A module can
+/// be transformed more than once, so fragments of the same module are
+/// combined.
+pub fn merge_client_reference_fragments(
+    fragments: impl IntoIterator<Item = ClientReferenceFragment>,
+) -> ClientReferenceManifest {
+    let mut manifest = ClientReferenceManifest::default();
+
+    for ClientReferenceFragment {
+        module_path,
+        exports,
+        chunking,
+    } in fragments
+    {
+        let references = manifest
+            .client_modules
+            .entry(module_path.clone())
+            .or_default();
+        for name in exports {
+            let reference = references
+                .entry(name.clone())
+                .or_insert_with(|| ClientReference {
+                    id: module_path.clone(),
+                    name,
+                    ..Default::default()
+                });
+            reference.r#async |= chunking.is_async;
+        }
+
+        if !chunking.css_imports.is_empty() {
+            let css_files = manifest.css_files.entry(module_path).or_default();
+            for css in chunking.css_imports {
+                if !css_files.contains(&css) {
+                    css_files.push(css);
+                }
+            }
+        }
+    }
+
+    manifest
+}
+
+pub(crate) fn chunking_hints(module: &Module, module_path: &str) -> ChunkingHints {
+    let mut finder = TopLevelAwaitFinder { found: false };
+    module.visit_with(&mut finder);
+
+    ChunkingHints {
+        is_async: finder.found,
+        css_imports: module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+                    src,
+                    type_only: false,
+                    ..
+                })) if is_stylesheet(&src.value) => {
+                    Some(resolve_stylesheet(module_path, &src.value))
+                }
+                _ => None,
+            })
+            .collect(),
+    }
+}
+
+fn resolve_stylesheet(module_path: &str, specifier: &str) -> String {
+    if !specifier.starts_with('.') {
+        return specifier.to_string();
+    }
+    // Keep queries like `?inline` as they are.
+    let (path, query) = specifier.split_at(specifier.find('?').unwrap_or(specifier.len()));
+    match Path::new(module_path).parent() {
+        Some(dir) => format!("{}{}", normalize(&dir.join(path)).to_string_lossy(), query),
+        None => specifier.to_string(),
+    }
+}
+
+fn is_stylesheet(specifier: &str) -> bool {
+    let path = specifier.split('?').next().unwrap_or(specifier);
+    [".css", ".scss", ".sass", ".less"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+struct TopLevelAwaitFinder {
+    found: bool,
+}
+
+impl Visit for TopLevelAwaitFinder {
+    noop_visit_type!();
+
+    fn visit_await_expr(&mut self, _: &AwaitExpr) {
+        self.found = true;
+    }
+
+    fn visit_for_of_stmt(&mut self, stmt: &ForOfStmt) {
+        self.found |= stmt.await_token.is_some();
+        stmt.visit_children_with(self);
+    }
+
+    // `await` in functions and classes doesn't make the module async.
+    fn visit_function(&mut self, _: &Function) {}
+
+    fn visit_arrow_expr(&mut self, _: &ArrowExpr) {}
+
+    fn visit_class(&mut self, _: &Class) {}
+}
+
+/// A module loaded by a `next/dynamic` call, recorded by `next_dynamic`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadableFragment {
+    /// `"<file> -> <specifier>"`, the key of `loadableGenerated.modules`.
+    pub key: String,
+    pub specifier: String,
+    /// `false` with `ssr: false`.
+    pub ssr: bool,
+    pub suspense: bool,
+    pub call_site: CallSite,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallSite {
+    pub file: String,
+    pub start: Location,
+    pub end: Location,
+}
+
+/// Keyed like `loadableGenerated.modules`.
+pub type LoadableManifest = BTreeMap<String, LoadableEntry>;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadableEntry {
+    pub id: String,
+    /// Filled in by the bundler once the chunks are known.
+    pub files: Vec<String>,
+    /// Whether any of the calls renders the module on the server.
+    pub ssr: bool,
+    pub suspense: bool,
+    pub call_sites: Vec<CallSite>,
+}
+
+/// Merges the fragments of all the files of a build into the loadable
+/// manifest.
+pub fn merge_loadable_fragments(
+    fragments: impl IntoIterator<Item = LoadableFragment>,
+) -> LoadableManifest {
+    let mut manifest = LoadableManifest::new();
+
+    for LoadableFragment {
+        key,
+        specifier,
+        ssr,
+        suspense,
+        call_site,
+    } in fragments
+    {
+        let entry = manifest.entry(key).or_insert_with(|| LoadableEntry {
+            id: specifier,
+            ..Default::default()
+        });
+        entry.ssr |= ssr;
+        entry.suspense |= suspense;
+        if !entry.call_sites.contains(&call_site) {
+            entry.call_sites.push(call_site);
+        }
+    }
+
+    manifest
+}
@@ -0,0 +1,178 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use swc_core::{
+    ecma::ast::*,
+    ecma::utils::find_pat_ids,
+    ecma::visit::{as_folder, noop_visit_mut_type, Fold, VisitMut},
+};
+
+use crate::manifests::{ClientReferenceFragment, LoadableFragment};
+
+/// Facts about a module which bundler plugins would otherwise have to parse
+/// the output again for. Recorded by the passes of `custom_before_pass` when
+/// `collectMetadata` is set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleMetadata {
+    /// Exported names, including `default`.
+    pub exports: Vec<String>,
+    /// Sources of `export * from '...'`.
+    pub star_exports: Vec<String>,
+    /// Whether the module starts with a "use client" directive.
+    pub is_client_entry: bool,
+    pub has_get_static_props: bool,
+    pub has_get_server_side_props: bool,
+    pub has_get_static_paths: bool,
+    /// Specifiers imported by `next/dynamic` calls.
+    pub dynamic_imports: Vec<String>,
+    /// The modules loaded by `next/dynamic` calls, for the loadable manifest.
+    pub loadable_modules: Vec<LoadableFragment>,
+    pub font_loader_calls: Vec<FontLoaderCall>,
+    /// Server Actions declared by the module, for the actions manifest.
+    pub server_actions: Vec<ServerAction>,
+    /// Route segment config of `app` layouts and pages which are Server
+    /// Components.
+    pub route_segment_config: Option<RouteSegmentConfig>,
+    /// The `metadata` export of a layout or page, when it is an object of
+    /// literals.
+    pub static_metadata: Option<Value>,
+    /// Set for "use client" modules on the server layer, where they are
+    /// replaced with references.
+    pub client_reference: Option<ClientReferenceFragment>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerAction {
+    /// Derived from the path of the module and `name`.
+    pub id: String,
+    /// The export, or the binding of a function with a "use server" directive.
+    /// Inline ones are named `$$INLINE_ACTION_` followed by their index.
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteSegmentConfig {
+    /// Seconds, or `false`.
+    pub revalidate: Option<Value>,
+    pub dynamic: Option<String>,
+    pub dynamic_params: Option<bool>,
+    pub fetch_cache: Option<String>,
+    pub runtime: Option<String>,
+    /// A region, or a list of them.
+    pub preferred_region: Option<Value>,
+    pub generate_static_params: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontLoaderCall {
+    pub loader: String,
+    /// The imported function, empty for default imports.
+    pub import: String,
+    pub arguments: Vec<Value>,
+    pub variable_name: Option<String>,
+}
+
+/// Records the exports and directives of the module as it was written, so it
+/// has to run before the passes which rewrite them.
+pub fn collect_metadata(metadata: Rc<RefCell<ModuleMetadata>>) -> impl Fold + VisitMut {
+    as_folder(MetadataCollector { metadata })
+}
+
+struct MetadataCollector {
+    metadata: Rc<RefCell<ModuleMetadata>>,
+}
+
+impl VisitMut for MetadataCollector {
+    noop_visit_mut_type!();
+
+    fn visit_mut_module(&mut self, module: &mut Module) {
+        let mut metadata = self.metadata.borrow_mut();
+        let metadata = &mut *metadata;
+
+        metadata.is_client_entry = module
+            .body
+            .iter()
+            .map_while(|item| match item {
+                ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match &**expr {
+                    Expr::Lit(Lit::Str(Str { value, .. })) => Some(value),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .any(|value| &**value == "use client");
+
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(decl) = item {
+                collect_exports(decl, &mut metadata.exports, &mut metadata.star_exports);
+            }
+        }
+
+        for export in &metadata.exports {
+            match &**export {
+                "getStaticProps" => metadata.has_get_static_props = true,
+                "getServerSideProps" => metadata.has_get_server_side_props = true,
+                "getStaticPaths" => metadata.has_get_static_paths = true,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Collects the names exported by `decl`, and the sources of `export *`.
+pub(crate) fn collect_exports(
+    decl: &ModuleDecl,
+    exports: &mut Vec<String>,
+    star_exports: &mut Vec<String>,
+) {
+    match decl {
+        ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+            Decl::Class(ClassDecl { ident, .. })
+            | Decl::Fn(FnDecl { ident, .. })
+            | Decl::TsEnum(box TsEnumDecl { id: ident, .. }) => {
+                exports.push(ident.sym.to_string());
+            }
+            Decl::Var(var) => {
+                let ids: Vec<Ident> = find_pat_ids(&var.decls);
+                exports.extend(ids.into_iter().map(|id| id.sym.to_string()));
+            }
+            _ => {}
+        },
+        ModuleDecl::ExportNamed(NamedExport {
+            specifiers,
+            type_only: false,
+            ..
+        }) => {
+            for specifier in specifiers {
+                let name = match specifier {
+                    ExportSpecifier::Named(ExportNamedSpecifier {
+                        is_type_only: true, ..
+                    }) => continue,
+                    ExportSpecifier::Named(ExportNamedSpecifier { orig, exported, .. }) => {
+                        exported.as_ref().unwrap_or(orig)
+                    }
+                    ExportSpecifier::Namespace(ExportNamespaceSpecifier { name, .. }) => name,
+                    ExportSpecifier::Default(ExportDefaultSpecifier { exported }) => {
+                        exports.push(exported.sym.to_string());
+                        continue;
+                    }
+                };
+                exports.push(match name {
+                    ModuleExportName::Ident(ident) => ident.sym.to_string(),
+                    ModuleExportName::Str(s) => s.value.to_string(),
+                });
+            }
+        }
+        ModuleDecl::ExportDefaultDecl(..) | ModuleDecl::ExportDefaultExpr(..) => {
+            exports.push("default".to_string());
+        }
+        ModuleDecl::ExportAll(ExportAll { src, .. }) => {
+            star_exports.push(src.value.to_string());
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,218 @@
+use std::{cell::Cell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{errors::HANDLER, Mark, Span, Spanned},
+    ecma::ast::*,
+    ecma::visit::{
+        as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitWith,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModuleFormat {
+    /// Only `import` and `export`.
+    Esm,
+    /// Only `require`, `module.exports` and `exports`.
+    #[serde(rename = "commonjs")]
+    CommonJs,
+    /// Both of the above.
+    Mixed,
+    /// Neither of the above.
+    Script,
+}
+
+impl ModuleFormat {
+    /// The serialized name of the format.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModuleFormat::Esm => "esm",
+            ModuleFormat::CommonJs => "commonjs",
+            ModuleFormat::Mixed => "mixed",
+            ModuleFormat::Script => "script",
+        }
+    }
+}
+
+/// Detects the format of a program which swc's resolver ran on with
+/// `unresolved_mark`, so `module`, `exports` and `require` bindings are not
+/// mistaken for the CommonJS globals.
+pub fn detect_module_format(program: &Program, unresolved_mark: Mark) -> ModuleFormat {
+    ModuleSyntax::find(program, unresolved_mark).format()
+}
+
+/// Whether the program has to be compiled as CommonJS for its exports to keep
+/// working. `require` calls alone also work in ES modules, but `exports`
+/// anywhere, like in UMD wrappers, and the `__esModule` flag of compiled
+/// modules need the CommonJS globals.
+pub fn needs_commonjs(program: &Program, unresolved_mark: Mark) -> bool {
+    ModuleSyntax::find(program, unresolved_mark)
+        .cjs_exports
+        .is_some()
+}
+
+/// Records the format of the module into `module_format`, and warns about
+/// modules which are compiled as CommonJS even though they have ES module
+/// syntax.
+pub fn module_format(
+    unresolved_mark: Mark,
+    module_format: Rc<Cell<Option<ModuleFormat>>>,
+) -> impl Fold + VisitMut {
+    as_folder(ModuleFormatChecker {
+        unresolved_mark,
+        module_format,
+    })
+}
+
+struct ModuleFormatChecker {
+    unresolved_mark: Mark,
+    module_format: Rc<Cell<Option<ModuleFormat>>>,
+}
+
+impl VisitMut for ModuleFormatChecker {
+    noop_visit_mut_type!();
+
+    fn visit_mut_program(&mut self, program: &mut Program) {
+        let syntax = ModuleSyntax::find(program, self.unresolved_mark);
+
+        if let (Some(esm), Some(cjs)) = (syntax.esm, syntax.cjs_exports) {
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_warn(
+                        cjs,
+                        "This module mixes CommonJS exports with ES module syntax, so it is \
+                         compiled as CommonJS. Use either `module.exports`, or `export`.",
+                    )
+                    .span_note(esm, "ES module syntax is used here")
+                    .emit()
+            });
+        }
+
+        self.module_format.set(Some(syntax.format()));
+    }
+}
+
+/// The first ES module and CommonJS syntax found in a module.
+struct ModuleSyntax {
+    unresolved_mark: Mark,
+    esm: Option<Span>,
+    /// `module.exports`, `exports` and strings mentioning `__esModule`, like
+    /// the interop helpers of compiled modules.
+    cjs_exports: Option<Span>,
+    /// `require` calls, and the above.
+    cjs: Option<Span>,
+}
+
+impl ModuleSyntax {
+    fn find(program: &Program, unresolved_mark: Mark) -> Self {
+        let mut syntax = ModuleSyntax {
+            unresolved_mark,
+            esm: None,
+            cjs_exports: None,
+            cjs: None,
+        };
+        program.visit_with(&mut syntax);
+        syntax
+    }
+
+    fn format(&self) -> ModuleFormat {
+        match (self.esm, self.cjs) {
+            (Some(..), Some(..)) => ModuleFormat::Mixed,
+            (Some(..), None) => ModuleFormat::Esm,
+            (None, Some(..)) => ModuleFormat::CommonJs,
+            (None, None) => ModuleFormat::Script,
+        }
+    }
+
+    fn is_global(&self, ident: &Ident, name: &str) -> bool {
+        &*ident.sym == name && ident.span.ctxt.outer() == self.unresolved_mark
+    }
+
+    fn found_esm(&mut self, span: Span) {
+        self.esm.get_or_insert(span);
+    }
+
+    fn found_cjs(&mut self, span: Span) {
+        self.cjs.get_or_insert(span);
+    }
+
+    fn found_cjs_exports(&mut self, span: Span) {
+        self.found_cjs(span);
+        self.cjs_exports.get_or_insert(span);
+    }
+}
+
+impl Visit for ModuleSyntax {
+    noop_visit_type!();
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        match decl {
+            ModuleDecl::Import(ImportDecl {
+                type_only: true, ..
+            })
+            | ModuleDecl::ExportNamed(NamedExport {
+                type_only: true, ..
+            })
+            | ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::TsInterface(..) | Decl::TsTypeAlias(..),
+                ..
+            })
+            | ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::TsInterfaceDecl(..),
+                ..
+            })
+            | ModuleDecl::TsNamespaceExport(..) => {}
+            // `import foo = require('foo')` and `export = foo`
+            ModuleDecl::TsImportEquals(..) => self.found_cjs(decl.span()),
+            ModuleDecl::TsExportAssignment(..) => self.found_cjs_exports(decl.span()),
+            _ => self.found_esm(decl.span()),
+        }
+
+        decl.visit_children_with(self);
+    }
+
+    fn visit_str(&mut self, s: &Str) {
+        if s.value.contains("__esModule") {
+            self.cjs_exports.get_or_insert(s.span);
+        }
+    }
+
+    fn visit_meta_prop_expr(&mut self, expr: &MetaPropExpr) {
+        if let MetaPropKind::ImportMeta = expr.kind {
+            self.found_esm(expr.span);
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match expr {
+            // `typeof exports`, which UMD wrappers check before using it.
+            Expr::Unary(UnaryExpr {
+                op: op!("typeof"),
+                arg: box Expr::Ident(..),
+                ..
+            }) => return,
+            // `exports.foo = ...` and `Object.defineProperty(exports, ...)`
+            Expr::Ident(ident) if self.is_global(ident, "exports") => {
+                self.found_cjs_exports(ident.span)
+            }
+            // `module.exports`
+            Expr::Member(MemberExpr {
+                obj: box Expr::Ident(obj),
+                prop: MemberProp::Ident(prop),
+                span,
+            }) if self.is_global(obj, "module") && &*prop.sym == "exports" => {
+                self.found_cjs_exports(*span)
+            }
+            // `require('foo')`
+            Expr::Call(CallExpr {
+                callee: Callee::Expr(box Expr::Ident(callee)),
+                span,
+                ..
+            }) if self.is_global(callee, "require") => self.found_cjs(*span),
+            _ => {}
+        }
+
+        expr.visit_children_with(self);
+    }
+}
@@ -1,62 +1,218 @@
-use std::path::{Path, PathBuf};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::Arc,
+};
 
 use pathdiff::diff_paths;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer};
 
 use swc_core::{
-    common::{errors::HANDLER, FileName, DUMMY_SP},
+    common::{errors::HANDLER, FileName, SourceMap, Span, DUMMY_SP},
     ecma::ast::{
-        ArrayLit, ArrowExpr, BinExpr, BinaryOp, BlockStmtOrExpr, Bool, CallExpr, Callee, Expr,
-        ExprOrSpread, Id, Ident, ImportDecl, ImportSpecifier, KeyValueProp, Lit, MemberExpr,
-        MemberProp, Null, ObjectLit, Prop, PropName, PropOrSpread, Str, Tpl,
+        ArrayLit, ArrowExpr, BinExpr, BinaryOp, BlockStmtOrExpr, Bool, CallExpr, Callee, Class,
+        Expr, ExprOrSpread, Function, GetterProp, Id, Ident, ImportDecl, ImportNamedSpecifier,
+        ImportSpecifier, KeyValueProp, Lit, MemberExpr, MemberProp, ModuleExportName, Null,
+        ObjectLit, Prop, PropName, PropOrSpread, SetterProp, Str, Tpl,
     },
-    ecma::atoms::js_word,
     ecma::utils::ExprFactory,
     ecma::visit::{Fold, FoldWith},
 };
 
+use crate::{
+    diagnostics::Location,
+    manifests::{CallSite, LoadableFragment},
+    metadata::ModuleMetadata,
+};
+
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Functions which behave like the default export of `next/dynamic`, like
+    /// wrappers re-exporting it.
+    #[serde(default)]
+    pub dynamic_functions: Vec<DynamicFunction>,
+    /// How production builds report calls inside functions and classes,
+    /// with an error by default. Development builds only warn about them.
+    #[serde(default)]
+    pub nested_calls: NestedCalls,
+    /// Set from the `bundler` option.
+    #[serde(skip)]
+    pub bundler: Bundler,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum NestedCalls {
+    #[default]
+    Error,
+    Warn,
+    Allow,
+}
+
+/// How production client builds reference the modules of `next/dynamic`
+/// calls in `loadableGenerated`.
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum Bundler {
+    /// `webpack: () => [require.resolveWeak('./a')]`
+    #[default]
+    Webpack,
+    /// `modules: ['file.js -> ./a']`, the keys used in development, which
+    /// the bundler resolves with the loadable manifest.
+    ModuleIds,
+    /// `webpack: () => [callee('./a')]`, with a function of the bundler
+    /// resolving a module without loading it, like `__bundler_resolve__`.
+    Custom {
+        #[serde(deserialize_with = "deserialize_callee")]
+        #[schemars(with = "String")]
+        callee: String,
+    },
+}
+
+/// The callee is emitted as an identifier or a chain of member accesses, so
+/// anything else would produce invalid code.
+fn deserialize_callee<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let callee = String::deserialize(deserializer)?;
+    let is_identifier = |part: &str| {
+        let mut chars = part.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    };
+    if callee.split('.').all(is_identifier) {
+        Ok(callee)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "`{}` is not a valid callee, expected an identifier or a member expression like \
+             `__bundler__.resolveWeak`",
+            callee
+        )))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DynamicFunction {
+    pub module: String,
+    /// The name of the export, `default` for the default export.
+    pub export: String,
+}
+
 pub fn next_dynamic(
+    cm: Arc<SourceMap>,
     is_development: bool,
     is_server: bool,
     is_server_components: bool,
     filename: FileName,
     pages_dir: Option<PathBuf>,
+    config: Config,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
 ) -> impl Fold {
     NextDynamicPatcher {
+        cm,
         is_development,
         is_server,
         is_server_components,
         pages_dir,
         filename,
+        config,
+        metadata,
         dynamic_bindings: vec![],
         is_next_dynamic_first_arg: false,
-        dynamically_imported_specifier: None,
+        dynamically_imported_specifiers: vec![],
+        scope_depth: 0,
     }
 }
 
-#[derive(Debug)]
 struct NextDynamicPatcher {
+    cm: Arc<SourceMap>,
     is_development: bool,
     is_server: bool,
     is_server_components: bool,
     pages_dir: Option<PathBuf>,
     filename: FileName,
+    config: Config,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
     dynamic_bindings: Vec<Id>,
     is_next_dynamic_first_arg: bool,
-    dynamically_imported_specifier: Option<String>,
+    dynamically_imported_specifiers: Vec<String>,
+    /// The number of functions and classes around the visited node.
+    scope_depth: usize,
 }
 
 impl Fold for NextDynamicPatcher {
+    fn fold_function(&mut self, function: Function) -> Function {
+        self.scope_depth += 1;
+        let function = function.fold_children_with(self);
+        self.scope_depth -= 1;
+        function
+    }
+
+    fn fold_arrow_expr(&mut self, arrow: ArrowExpr) -> ArrowExpr {
+        self.scope_depth += 1;
+        let arrow = arrow.fold_children_with(self);
+        self.scope_depth -= 1;
+        arrow
+    }
+
+    fn fold_class(&mut self, class: Class) -> Class {
+        self.scope_depth += 1;
+        let class = class.fold_children_with(self);
+        self.scope_depth -= 1;
+        class
+    }
+
+    // Accessors of object literals are functions too, unlike other props.
+    fn fold_getter_prop(&mut self, prop: GetterProp) -> GetterProp {
+        self.scope_depth += 1;
+        let prop = prop.fold_children_with(self);
+        self.scope_depth -= 1;
+        prop
+    }
+
+    fn fold_setter_prop(&mut self, prop: SetterProp) -> SetterProp {
+        self.scope_depth += 1;
+        let prop = prop.fold_children_with(self);
+        self.scope_depth -= 1;
+        prop
+    }
+
     fn fold_import_decl(&mut self, decl: ImportDecl) -> ImportDecl {
         let ImportDecl {
             ref src,
             ref specifiers,
             ..
         } = decl;
-        if &src.value == "next/dynamic" {
-            for specifier in specifiers {
-                if let ImportSpecifier::Default(default_specifier) = specifier {
-                    self.dynamic_bindings.push(default_specifier.local.to_id());
+        let exports: Vec<&str> = self
+            .config
+            .dynamic_functions
+            .iter()
+            .filter(|function| function.module == *src.value)
+            .map(|function| &*function.export)
+            .chain((&src.value == "next/dynamic").then_some("default"))
+            .collect();
+        for specifier in specifiers {
+            let (imported, local) = match specifier {
+                ImportSpecifier::Default(default_specifier) => {
+                    ("default", &default_specifier.local)
                 }
+                // `import { default as dynamic } from 'next/dynamic'`
+                ImportSpecifier::Named(ImportNamedSpecifier {
+                    local, imported, ..
+                }) => match imported {
+                    Some(ModuleExportName::Ident(imported)) => (&*imported.sym, local),
+                    Some(ModuleExportName::Str(imported)) => (&*imported.value, local),
+                    None => (&*local.sym, local),
+                },
+                ImportSpecifier::Namespace(..) => continue,
+            };
+            if exports.contains(&imported) {
+                self.dynamic_bindings.push(local.to_id());
             }
         }
 
@@ -68,10 +224,11 @@ impl Fold for NextDynamicPatcher {
             if let Callee::Import(..) = &expr.callee {
                 match &*expr.args[0].expr {
                     Expr::Lit(Lit::Str(Str { value, .. })) => {
-                        self.dynamically_imported_specifier = Some(value.to_string());
+                        self.dynamically_imported_specifiers.push(value.to_string());
                     }
                     Expr::Tpl(Tpl { exprs, quasis, .. }) if exprs.is_empty() => {
-                        self.dynamically_imported_specifier = Some(quasis[0].raw.to_string());
+                        self.dynamically_imported_specifiers
+                            .push(quasis[0].raw.to_string());
                     }
                     _ => {}
                 }
@@ -82,6 +239,9 @@ impl Fold for NextDynamicPatcher {
         if let Callee::Expr(i) = &expr.callee {
             if let Expr::Ident(identifier) = &**i {
                 if self.dynamic_bindings.contains(&identifier.to_id()) {
+                    if self.scope_depth > 0 {
+                        self.report_nested_call(identifier);
+                    }
                     if expr.args.is_empty() {
                         HANDLER.with(|handler| {
                             handler
@@ -124,9 +284,23 @@ impl Fold for NextDynamicPatcher {
                     expr.args[0].expr = expr.args[0].expr.clone().fold_with(self);
                     self.is_next_dynamic_first_arg = false;
 
-                    if self.dynamically_imported_specifier.is_none() {
+                    // `() => Promise.all([import('./a'), import('./b')])` loads
+                    // both modules, so both have to be preloaded.
+                    let mut specifiers: Vec<String> = vec![];
+                    for specifier in self.dynamically_imported_specifiers.drain(..) {
+                        if !specifiers.contains(&specifier) {
+                            specifiers.push(specifier);
+                        }
+                    }
+                    if specifiers.is_empty() {
                         return expr;
                     }
+                    if let Some(metadata) = &self.metadata {
+                        metadata
+                            .borrow_mut()
+                            .dynamic_imports
+                            .extend(specifiers.iter().cloned());
+                    }
 
                     // dev client or server:
                     // loadableGenerated: {
@@ -138,39 +312,42 @@ impl Fold for NextDynamicPatcher {
                     //   webpack: () => [require.resolveWeak('../components/hello')],
                     let generated = Box::new(Expr::Object(ObjectLit {
                         span: DUMMY_SP,
-                        props: if self.is_development || self.is_server {
+                        props: if self.is_development
+                            || self.is_server
+                            || matches!(self.config.bundler, Bundler::ModuleIds)
+                        {
                             vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
                                 key: PropName::Ident(Ident::new("modules".into(), DUMMY_SP)),
                                 value: Box::new(Expr::Array(ArrayLit {
-                                    elems: vec![Some(ExprOrSpread {
-                                        expr: Box::new(Expr::Bin(BinExpr {
-                                            span: DUMMY_SP,
-                                            op: BinaryOp::Add,
-                                            left: Box::new(Expr::Lit(Lit::Str(Str {
-                                                value: format!(
-                                                    "{} -> ",
-                                                    rel_filename(
-                                                        self.pages_dir.as_deref(),
-                                                        &self.filename
-                                                    )
-                                                )
-                                                .into(),
-                                                span: DUMMY_SP,
-                                                raw: None,
-                                            }))),
-                                            right: Box::new(Expr::Lit(Lit::Str(Str {
-                                                value: self
-                                                    .dynamically_imported_specifier
-                                                    .as_ref()
-                                                    .unwrap()
-                                                    .clone()
-                                                    .into(),
-                                                span: DUMMY_SP,
-                                                raw: None,
-                                            }))),
-                                        })),
-                                        spread: None,
-                                    })],
+                                    elems: specifiers
+                                        .iter()
+                                        .map(|specifier| {
+                                            Some(ExprOrSpread {
+                                                expr: Box::new(Expr::Bin(BinExpr {
+                                                    span: DUMMY_SP,
+                                                    op: BinaryOp::Add,
+                                                    left: Box::new(Expr::Lit(Lit::Str(Str {
+                                                        value: format!(
+                                                            "{} -> ",
+                                                            rel_filename(
+                                                                self.pages_dir.as_deref(),
+                                                                &self.filename
+                                                            )
+                                                        )
+                                                        .into(),
+                                                        span: DUMMY_SP,
+                                                        raw: None,
+                                                    }))),
+                                                    right: Box::new(Expr::Lit(Lit::Str(Str {
+                                                        value: specifier.clone().into(),
+                                                        span: DUMMY_SP,
+                                                        raw: None,
+                                                    }))),
+                                                })),
+                                                spread: None,
+                                            })
+                                        })
+                                        .collect(),
                                     span: DUMMY_SP,
                                 })),
                             })))]
@@ -180,41 +357,29 @@ impl Fold for NextDynamicPatcher {
                                 value: Box::new(Expr::Arrow(ArrowExpr {
                                     params: vec![],
                                     body: BlockStmtOrExpr::Expr(Box::new(Expr::Array(ArrayLit {
-                                        elems: vec![Some(ExprOrSpread {
-                                            expr: Box::new(Expr::Call(CallExpr {
-                                                callee: Callee::Expr(Box::new(Expr::Member(
-                                                    MemberExpr {
-                                                        obj: Box::new(Expr::Ident(Ident {
-                                                            sym: js_word!("require"),
-                                                            span: DUMMY_SP,
-                                                            optional: false,
-                                                        })),
-                                                        prop: MemberProp::Ident(Ident {
-                                                            sym: "resolveWeak".into(),
-                                                            span: DUMMY_SP,
-                                                            optional: false,
-                                                        }),
-                                                        span: DUMMY_SP,
-                                                    },
-                                                ))),
-                                                args: vec![ExprOrSpread {
-                                                    expr: Box::new(Expr::Lit(Lit::Str(Str {
-                                                        value: self
-                                                            .dynamically_imported_specifier
-                                                            .as_ref()
-                                                            .unwrap()
-                                                            .clone()
-                                                            .into(),
+                                        elems: specifiers
+                                            .iter()
+                                            .map(|specifier| {
+                                                Some(ExprOrSpread {
+                                                    expr: Box::new(Expr::Call(CallExpr {
+                                                        callee: Callee::Expr(self.resolve_weak()),
+                                                        args: vec![ExprOrSpread {
+                                                            expr: Box::new(Expr::Lit(Lit::Str(
+                                                                Str {
+                                                                    value: specifier.clone().into(),
+                                                                    span: DUMMY_SP,
+                                                                    raw: None,
+                                                                },
+                                                            ))),
+                                                            spread: None,
+                                                        }],
                                                         span: DUMMY_SP,
-                                                        raw: None,
-                                                    }))),
+                                                        type_args: None,
+                                                    })),
                                                     spread: None,
-                                                }],
-                                                span: DUMMY_SP,
-                                                type_args: None,
-                                            })),
-                                            spread: None,
-                                        })],
+                                                })
+                                            })
+                                            .collect(),
                                         span: DUMMY_SP,
                                     }))),
                                     is_async: false,
@@ -235,6 +400,7 @@ impl Fold for NextDynamicPatcher {
 
                     let mut has_ssr_false = false;
                     let mut has_suspense = false;
+                    let mut has_loading = false;
 
                     if expr.args.len() == 2 {
                         if let Expr::Object(ObjectLit {
@@ -276,6 +442,9 @@ impl Fold for NextDynamicPatcher {
                                                 has_suspense = true
                                             }
                                         }
+                                        if sym == "loading" {
+                                            has_loading = true
+                                        }
                                     }
                                 }
                             }
@@ -283,6 +452,49 @@ impl Fold for NextDynamicPatcher {
                         }
                     }
 
+                    // Both options are dropped at runtime when suspense is enabled.
+                    if has_suspense && has_ssr_false {
+                        HANDLER.with(|handler| {
+                            handler
+                                .struct_span_warn(
+                                    identifier.span,
+                                    "\"ssr: false\" is ignored by next/dynamic because \"suspense\" \
+                                     is enabled.\nRead more: \
+                                     https://nextjs.org/docs/messages/invalid-dynamic-suspense",
+                                )
+                                .emit()
+                        });
+                    }
+                    if has_suspense && has_loading {
+                        HANDLER.with(|handler| {
+                            handler
+                                .struct_span_warn(
+                                    identifier.span,
+                                    "\"loading\" is ignored by next/dynamic because \"suspense\" is \
+                                     enabled.\nPlace your loading element in the \"fallback\" prop \
+                                     of your suspense boundary instead.\nRead more: \
+                                     https://nextjs.org/docs/messages/invalid-dynamic-suspense",
+                                )
+                                .emit()
+                        });
+                    }
+
+                    if let Some(metadata) = &self.metadata {
+                        let call_site = self.call_site(expr.span);
+                        let fragments = specifiers.iter().map(|specifier| LoadableFragment {
+                            key: format!(
+                                "{} -> {}",
+                                rel_filename(self.pages_dir.as_deref(), &self.filename),
+                                specifier
+                            ),
+                            specifier: specifier.clone(),
+                            ssr: !has_ssr_false,
+                            suspense: has_suspense,
+                            call_site: call_site.clone(),
+                        });
+                        metadata.borrow_mut().loadable_modules.extend(fragments);
+                    }
+
                     // Don't strip the `loader` argument if suspense is true
                     // See https://github.com/vercel/next.js/issues/36636 for background.
 
@@ -310,7 +522,6 @@ impl Fold for NextDynamicPatcher {
                     } else {
                         expr.args.push(second_arg)
                     }
-                    self.dynamically_imported_specifier = None;
                 }
             }
         }
@@ -318,6 +529,64 @@ impl Fold for NextDynamicPatcher {
     }
 }
 
+impl NextDynamicPatcher {
+    fn report_nested_call(&self, identifier: &Ident) {
+        let message = format!(
+            "next/dynamic has to be called at the top level of the module. Calling it inside a \
+             function or a class creates a new component every time, which remounts it and breaks \
+             preloading.\nMove the `{}()` call out of the function, next to the imports.",
+            identifier.sym
+        );
+        HANDLER.with(
+            |handler| match (self.is_development, self.config.nested_calls) {
+                (_, NestedCalls::Allow) => {}
+                (false, NestedCalls::Error) => {
+                    handler.struct_span_err(identifier.span, &message).emit()
+                }
+                _ => handler.struct_span_warn(identifier.span, &message).emit(),
+            },
+        );
+    }
+
+    /// `require.resolveWeak`, or the custom callee of the bundler, like
+    /// `__bundler__.resolveWeak`.
+    fn resolve_weak(&self) -> Box<Expr> {
+        let path = match &self.config.bundler {
+            Bundler::Custom { callee } => callee.as_str(),
+            _ => "require.resolveWeak",
+        };
+        let mut parts = path.split('.');
+        let mut callee = Box::new(Expr::Ident(Ident::new(
+            parts.next().unwrap_or_default().into(),
+            DUMMY_SP,
+        )));
+        for part in parts {
+            callee = Box::new(Expr::Member(MemberExpr {
+                obj: callee,
+                prop: MemberProp::Ident(Ident::new(part.into(), DUMMY_SP)),
+                span: DUMMY_SP,
+            }));
+        }
+        callee
+    }
+
+    fn call_site(&self, span: Span) -> CallSite {
+        let start = self.cm.lookup_char_pos(span.lo);
+        let end = self.cm.lookup_char_pos(span.hi);
+        CallSite {
+            file: self.filename.to_string(),
+            start: Location {
+                line: start.line,
+                column: start.col.0,
+            },
+            end: Location {
+                line: end.line,
+                column: end.col.0,
+            },
+        }
+    }
+}
+
 fn rel_filename(base: Option<&Path>, file: &FileName) -> String {
     let base = match base {
         Some(v) => v,
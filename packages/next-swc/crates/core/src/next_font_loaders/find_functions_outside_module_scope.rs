@@ -1,3 +1,4 @@
+use fxhash::FxHashSet;
 use swc_core::common::errors::HANDLER;
 use swc_core::ecma::ast::*;
 use swc_core::ecma::visit::noop_visit_type;
@@ -5,18 +6,27 @@ use swc_core::ecma::visit::Visit;
 
 pub struct FindFunctionsOutsideModuleScope<'a> {
     pub state: &'a super::State,
+    /// Font functions which are referenced outside of their import.
+    pub used: FxHashSet<Id>,
 }
 
 impl<'a> Visit for FindFunctionsOutsideModuleScope<'a> {
     noop_visit_type!();
 
     fn visit_ident(&mut self, ident: &Ident) {
-        if self.state.font_functions.get(&ident.to_id()).is_some()
-            && self
-                .state
-                .font_functions_in_allowed_scope
-                .get(&ident.span.lo)
-                .is_none()
+        let font_function = match self.state.font_functions.get(&ident.to_id()) {
+            Some(font_function) => font_function,
+            None => return,
+        };
+        if font_function.span.lo != ident.span.lo {
+            self.used.insert(ident.to_id());
+        }
+
+        if self
+            .state
+            .font_functions_in_allowed_scope
+            .get(&ident.span.lo)
+            .is_none()
         {
             HANDLER.with(|handler| {
                 handler
@@ -36,6 +36,7 @@ impl<'a> Visit for FontFunctionsCollector<'a> {
                             super::FontFunction {
                                 loader: import_decl.src.value.clone(),
                                 function_name: Some(function_name),
+                                span: local.span,
                             },
                         );
                     }
@@ -48,6 +49,7 @@ impl<'a> Visit for FontFunctionsCollector<'a> {
                             super::FontFunction {
                                 loader: import_decl.src.value.clone(),
                                 function_name: None,
+                                span: local.span,
                             },
                         );
                     }
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+
 use serde_json::Value;
 use swc_core::common::errors::HANDLER;
 use swc_core::common::{Spanned, DUMMY_SP};
@@ -5,9 +7,12 @@ use swc_core::ecma::ast::*;
 use swc_core::ecma::atoms::JsWord;
 use swc_core::ecma::visit::{noop_visit_type, Visit};
 
+use crate::metadata::{FontLoaderCall, ModuleMetadata};
+
 pub struct FontImportsGenerator<'a> {
     pub state: &'a mut super::State,
     pub relative_path: &'a str,
+    pub metadata: Option<&'a RefCell<ModuleMetadata>>,
 }
 
 impl<'a> FontImportsGenerator<'a> {
@@ -51,6 +56,20 @@ impl<'a> FontImportsGenerator<'a> {
                         );
                         query_json_values
                             .insert(String::from("import"), Value::String(function_name));
+                        if let Some(metadata) = self.metadata {
+                            metadata
+                                .borrow_mut()
+                                .font_loader_calls
+                                .push(FontLoaderCall {
+                                    loader: font_function.loader.to_string(),
+                                    import: function_name.clone(),
+                                    arguments: json.clone(),
+                                    variable_name: variable_name
+                                        .as_ref()
+                                        .ok()
+                                        .map(|i| i.sym.to_string()),
+                                });
+                        }
                         query_json_values.insert(String::from("arguments"), Value::Array(json));
                         if let Ok(ident) = variable_name {
                             query_json_values.insert(
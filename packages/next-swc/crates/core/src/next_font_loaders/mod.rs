@@ -1,7 +1,10 @@
+use std::{cell::RefCell, rc::Rc};
+
 use fxhash::FxHashSet;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use swc_core::{
-    common::{collections::AHashMap, BytePos, Spanned},
+    common::{collections::AHashMap, errors::HANDLER, BytePos, Span, Spanned},
     ecma::{
         ast::Id,
         visit::{as_folder, noop_visit_mut_type, Fold, VisitMut, VisitWith},
@@ -9,20 +12,28 @@ use swc_core::{
     ecma::{ast::ModuleItem, atoms::JsWord},
 };
 
+use crate::metadata::ModuleMetadata;
+
 mod find_functions_outside_module_scope;
 mod font_functions_collector;
 mod font_imports_generator;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields, rename_all = "camelCase")]
 pub struct Config {
+    #[schemars(with = "Vec<String>")]
     pub font_loaders: Vec<JsWord>,
+    #[schemars(with = "String")]
     pub relative_file_path_from_root: JsWord,
 }
 
-pub fn next_font_loaders(config: Config) -> impl Fold + VisitMut {
+pub fn next_font_loaders(
+    config: Config,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
+) -> impl Fold + VisitMut {
     as_folder(NextFontLoaders {
         config,
+        metadata,
         state: State {
             ..Default::default()
         },
@@ -33,6 +44,8 @@ pub fn next_font_loaders(config: Config) -> impl Fold + VisitMut {
 pub struct FontFunction {
     loader: JsWord,
     function_name: Option<JsWord>,
+    /// The local name in the import.
+    span: Span,
 }
 #[derive(Debug, Default)]
 pub struct State {
@@ -45,6 +58,7 @@ pub struct State {
 
 struct NextFontLoaders {
     config: Config,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
     state: State,
 }
 
@@ -64,6 +78,7 @@ impl VisitMut for NextFontLoaders {
             let mut import_generator = font_imports_generator::FontImportsGenerator {
                 state: &mut self.state,
                 relative_path: &self.config.relative_file_path_from_root,
+                metadata: self.metadata.as_deref(),
             };
             items.visit_with(&mut import_generator);
 
@@ -71,9 +86,34 @@ impl VisitMut for NextFontLoaders {
             let mut wrong_scope =
                 find_functions_outside_module_scope::FindFunctionsOutsideModuleScope {
                     state: &self.state,
+                    used: Default::default(),
                 };
             items.visit_with(&mut wrong_scope);
 
+            // Unused imports don't load any font, which is likely a mistake.
+            let mut unused: Vec<_> = self
+                .state
+                .font_functions
+                .iter()
+                .filter(|(id, _)| !wrong_scope.used.contains(id))
+                .map(|(id, font_function)| (font_function.span, id.0.clone()))
+                .collect();
+            unused.sort_by_key(|(span, _)| span.lo);
+            for (span, name) in unused {
+                HANDLER.with(|handler| {
+                    handler
+                        .struct_span_warn(
+                            span,
+                            &format!(
+                                "`{}` is imported from a font loader but never called, so no font \
+                                 is loaded.",
+                                name
+                            ),
+                        )
+                        .emit()
+                });
+            }
+
             // Remove marked module items
             items.retain(|item| !self.state.removeable_module_items.contains(&item.span_lo()));
 
@@ -0,0 +1,203 @@
+use std::{collections::HashMap, fmt};
+
+use once_cell::sync::Lazy;
+use schemars::{
+    schema::{RootSchema, Schema},
+    JsonSchema,
+};
+use serde_json::Value;
+
+use crate::TransformOptions;
+
+/// Top level fields of `swc_core::base::config::Options` and its flattened
+/// `Config`, which `TransformOptions` flattens in turn. They have to be kept
+/// in sync when swc is upgraded.
+pub const SWC_OPTIONS: &[&str] = &[
+    "$schema",
+    "caller",
+    "configFile",
+    "cwd",
+    "emitSourceMapColumns",
+    "env",
+    "envName",
+    "error",
+    "exclude",
+    "experimental",
+    "filename",
+    "inlineSourcesContent",
+    "inputSourceMap",
+    "isModule",
+    "jsc",
+    "minify",
+    "module",
+    "outputPath",
+    "root",
+    "rootMode",
+    "sourceFileName",
+    "sourceMaps",
+    "sourceRoot",
+    "swcrc",
+    "swcrcRoots",
+    "test",
+];
+
+static KNOWN_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    transform_options_schema()
+        .schema
+        .object
+        .map(|object| object.properties.into_keys().collect())
+        .unwrap_or_default()
+});
+
+/// JSON Schema of `TransformOptions`. The options of swc are listed without
+/// constraints, as swc validates them itself.
+pub fn transform_options_schema() -> RootSchema {
+    let mut schema = schemars::schema_for!(TransformOptions);
+    let object = schema.schema.object();
+    for key in SWC_OPTIONS {
+        object
+            .properties
+            .entry(key.to_string())
+            .or_insert(Schema::Bool(true));
+    }
+    schema
+}
+
+/// Schema of `styled_components::Config`, which does not implement
+/// `JsonSchema` itself. It has to be kept in sync when the crate is upgraded.
+#[derive(JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StyledComponentsConfig {
+    pub display_name: Option<bool>,
+    pub ssr: Option<bool>,
+    pub file_name: Option<bool>,
+    pub meaningless_file_names: Option<Vec<String>>,
+    pub namespace: Option<String>,
+    pub top_level_import_paths: Option<Vec<String>>,
+    pub transpile_template_literals: Option<bool>,
+    pub minify: Option<bool>,
+    pub pure: Option<bool>,
+    pub css_prop: Option<bool>,
+}
+
+/// Schema of `swc_emotion::EmotionOptions`, see `StyledComponentsConfig`.
+#[derive(JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionOptions {
+    pub enabled: Option<bool>,
+    pub sourcemap: Option<bool>,
+    pub auto_label: Option<bool>,
+    pub label_format: Option<String>,
+    /// Exports of other packages which re-export emotion, by package.
+    pub import_map: Option<HashMap<String, HashMap<String, EmotionImportItem>>>,
+}
+
+#[derive(JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EmotionImportItem {
+    pub canonical_import: (String, String),
+    pub styled_base_import: Option<(String, String)>,
+}
+
+/// Schema of `modularize_imports::Config`, see `StyledComponentsConfig`.
+#[derive(JsonSchema)]
+#[serde(transparent)]
+pub struct ModularizeImportsConfig {
+    pub packages: HashMap<String, ModularizeImportsPackage>,
+}
+
+#[derive(JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ModularizeImportsPackage {
+    pub transform: String,
+    pub prevent_full_import: Option<bool>,
+    pub skip_default_conversion: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct OptionError {
+    /// Like `styledComponents.displayName`, empty for the options themselves.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for OptionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Invalid options: {}", self.message)
+        } else {
+            write!(f, "Invalid option `{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for OptionError {}
+
+impl TransformOptions {
+    /// Deserializes the options, reporting where an invalid value was found.
+    /// With `strictOptions` set, unknown fields are rejected too, instead of
+    /// being silently ignored.
+    pub fn from_json(value: Value) -> Result<Self, Vec<OptionError>> {
+        let strict = value
+            .get("strictOptions")
+            .and_then(Value::as_bool)
+            .unwrap_or_default();
+
+        let mut errors = vec![];
+        let options = if strict {
+            // Fields unknown to `TransformOptions` are passed on to swc, which
+            // ignores the ones it does not know either.
+            if let Some(object) = value.as_object() {
+                errors.extend(
+                    object
+                        .keys()
+                        .filter(|key| !KNOWN_OPTIONS.contains(key))
+                        .map(|key| OptionError::unknown_field(key.clone())),
+                );
+            }
+
+            let mut ignored = vec![];
+            let mut on_ignored = |path: serde_ignored::Path| ignored.push(path.to_string());
+            let options = serde_path_to_error::deserialize(serde_ignored::Deserializer::new(
+                &value,
+                &mut on_ignored,
+            ));
+            errors.extend(ignored.into_iter().map(OptionError::unknown_field));
+            options
+        } else {
+            serde_path_to_error::deserialize(&value)
+        };
+
+        match options {
+            Ok(options) if errors.is_empty() => Ok(options),
+            Ok(..) => Err(errors),
+            Err(err) => {
+                let path = err.path().to_string();
+                errors.push(OptionError {
+                    path: if path == "." { String::new() } else { path },
+                    message: err.into_inner().to_string(),
+                });
+                Err(errors)
+            }
+        }
+    }
+
+    pub fn from_slice(json: &[u8]) -> Result<Self, Vec<OptionError>> {
+        let value = serde_json::from_slice(json).map_err(|err: serde_json::Error| {
+            vec![OptionError {
+                path: String::new(),
+                message: err.to_string(),
+            }]
+        })?;
+        Self::from_json(value)
+    }
+}
+
+impl OptionError {
+    fn unknown_field(path: String) -> Self {
+        OptionError {
+            path,
+            message: "unknown field".to_string(),
+        }
+    }
+}
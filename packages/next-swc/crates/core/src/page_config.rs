@@ -98,11 +98,18 @@ impl Fold for PageConfig {
                                                         if *value && self.is_page_file {
                                                             self.drop_bundle = true;
                                                         }
-                                                    } else if let Expr::Lit(Lit::Str(_)) =
+                                                    } else if let Expr::Lit(Lit::Str(s)) =
                                                         &*kv.value
                                                     {
                                                         // Do not replace
                                                         // bundle
+                                                        if &s.value != "hybrid" {
+                                                            self.handle_warning(
+                                                                "`amp` only accepts `true` or \
+                                                                 \"hybrid\".",
+                                                                s.span,
+                                                            );
+                                                        }
                                                     } else {
                                                         self.handle_error(
                                                             "Invalid value found.",
@@ -175,4 +182,12 @@ impl PageConfig {
             HANDLER.with(|handler| handler.struct_span_err(span, &message).emit());
         }
     }
+
+    fn handle_warning(&mut self, details: &str, span: Span) {
+        if self.is_page_file {
+            let message = format!("Unexpected page config value found. {} \
+      See: https://nextjs.org/docs/messages/invalid-page-config", details);
+            HANDLER.with(|handler| handler.struct_span_warn(span, &message).emit());
+        }
+    }
 }
@@ -0,0 +1,160 @@
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use serde::Serialize;
+use swc_core::{
+    common::FileName,
+    ecma::ast::*,
+    ecma::visit::{noop_visit_type, Fold, Visit, VisitWith},
+};
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PassStats {
+    pub name: &'static str,
+    /// Wall time of the pass. Not measured on wasm, which has no clock.
+    pub duration_ms: Option<f64>,
+    /// Number of expressions, statements and module declarations before and
+    /// after the pass.
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+}
+
+/// Wraps the passes of `custom_before_pass` in `tracing` spans, and records
+/// their stats if `stats` is set.
+pub struct PassTracer {
+    filename: String,
+    stats: Option<Rc<RefCell<Vec<PassStats>>>>,
+}
+
+impl PassTracer {
+    pub fn new(filename: &FileName, stats: Option<Rc<RefCell<Vec<PassStats>>>>) -> Self {
+        PassTracer {
+            filename: filename.to_string(),
+            stats,
+        }
+    }
+
+    pub fn wrap<F: Fold>(&self, name: &'static str, pass: F) -> Traced<F> {
+        Traced {
+            name,
+            filename: self.filename.clone(),
+            stats: self.stats.clone(),
+            pass,
+        }
+    }
+}
+
+pub struct Traced<F> {
+    name: &'static str,
+    filename: String,
+    stats: Option<Rc<RefCell<Vec<PassStats>>>>,
+    pass: F,
+}
+
+impl<F: Fold> Traced<F> {
+    fn run<N>(&mut self, node: N, fold: impl FnOnce(&mut F, N) -> N) -> N
+    where
+        N: VisitWith<NodeCounter>,
+    {
+        let _span = tracing::info_span!(
+            "next_swc_pass",
+            pass = self.name,
+            filename = self.filename.as_str()
+        )
+        .entered();
+
+        let stats = match &self.stats {
+            Some(stats) => stats.clone(),
+            None => return fold(&mut self.pass, node),
+        };
+
+        let nodes_before = count_nodes(&node);
+        #[cfg(not(target_arch = "wasm32"))]
+        let start = Instant::now();
+
+        let node = fold(&mut self.pass, node);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let duration_ms = Some(start.elapsed().as_secs_f64() * 1000.0);
+        #[cfg(target_arch = "wasm32")]
+        let duration_ms = None;
+
+        stats.borrow_mut().push(PassStats {
+            name: self.name,
+            duration_ms,
+            nodes_before,
+            nodes_after: count_nodes(&node),
+        });
+        node
+    }
+}
+
+/// Every node a pass can be started from is handed to the wrapped pass as a
+/// whole, which then visits its children itself. Falling back to the default
+/// methods would instead skip the wrapped pass for nodes like `Vec<Stmt>`.
+impl<F: Fold> Fold for Traced<F> {
+    fn fold_program(&mut self, program: Program) -> Program {
+        self.run(program, |pass, program| pass.fold_program(program))
+    }
+
+    fn fold_module(&mut self, module: Module) -> Module {
+        self.run(module, |pass, module| pass.fold_module(module))
+    }
+
+    fn fold_script(&mut self, script: Script) -> Script {
+        self.run(script, |pass, script| pass.fold_script(script))
+    }
+
+    fn fold_module_items(&mut self, items: Vec<ModuleItem>) -> Vec<ModuleItem> {
+        self.run(items, |pass, items| pass.fold_module_items(items))
+    }
+
+    fn fold_module_item(&mut self, item: ModuleItem) -> ModuleItem {
+        self.run(item, |pass, item| pass.fold_module_item(item))
+    }
+
+    fn fold_stmts(&mut self, stmts: Vec<Stmt>) -> Vec<Stmt> {
+        self.run(stmts, |pass, stmts| pass.fold_stmts(stmts))
+    }
+
+    fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+        self.run(stmt, |pass, stmt| pass.fold_stmt(stmt))
+    }
+
+    fn fold_expr(&mut self, expr: Expr) -> Expr {
+        self.run(expr, |pass, expr| pass.fold_expr(expr))
+    }
+}
+
+#[derive(Default)]
+pub struct NodeCounter {
+    count: usize,
+}
+
+impl Visit for NodeCounter {
+    noop_visit_type!();
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        self.count += 1;
+        expr.visit_children_with(self);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        self.count += 1;
+        stmt.visit_children_with(self);
+    }
+
+    fn visit_module_decl(&mut self, decl: &ModuleDecl) {
+        self.count += 1;
+        decl.visit_children_with(self);
+    }
+}
+
+fn count_nodes<N: VisitWith<NodeCounter>>(node: &N) -> usize {
+    let mut counter = NodeCounter::default();
+    node.visit_with(&mut counter);
+    counter.count
+}
@@ -0,0 +1,130 @@
+use std::{path::PathBuf, sync::Arc};
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use swc_core::{
+    common::{errors::HANDLER, FileName, Mark, SourceMap, DUMMY_SP},
+    ecma::ast::Program,
+    ecma::visit::Fold,
+};
+
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields, rename_all = "camelCase")]
+pub struct PluginConfig {
+    /// Path of the compiled `.wasm` plugin.
+    pub path: PathBuf,
+    /// Passed to the plugin as its configuration.
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+/// Runs swc wasm plugins, in order. `custom_before_pass` runs them after all
+/// of the built-in Next passes, so they see the same program swc would.
+///
+/// `unresolved_mark` has to be the mark swc's resolver applied to the
+/// program, so plugins can tell references to globals apart from bindings.
+pub fn plugins(
+    cm: Arc<SourceMap>,
+    filename: FileName,
+    env_name: &str,
+    plugins: Vec<PluginConfig>,
+    unresolved_mark: Mark,
+) -> impl Fold {
+    Plugins {
+        cm,
+        filename,
+        env_name: env_name.to_string(),
+        plugins,
+        unresolved_mark,
+    }
+}
+
+struct Plugins {
+    #[cfg_attr(not(feature = "plugin"), allow(unused))]
+    cm: Arc<SourceMap>,
+    #[cfg_attr(not(feature = "plugin"), allow(unused))]
+    filename: FileName,
+    #[cfg_attr(not(feature = "plugin"), allow(unused))]
+    env_name: String,
+    plugins: Vec<PluginConfig>,
+    #[cfg_attr(not(feature = "plugin"), allow(unused))]
+    unresolved_mark: Mark,
+}
+
+impl Fold for Plugins {
+    fn fold_program(&mut self, program: Program) -> Program {
+        if self.plugins.is_empty() {
+            return program;
+        }
+
+        match self.apply(&program) {
+            Ok(program) => program,
+            Err(message) => {
+                HANDLER.with(|handler| handler.struct_span_err(DUMMY_SP, &message).emit());
+                program
+            }
+        }
+    }
+}
+
+impl Plugins {
+    #[cfg(feature = "plugin")]
+    fn apply(&self, program: &Program) -> Result<Program, String> {
+        use swc_core::{
+            common::plugin::{
+                metadata::TransformPluginMetadataContext,
+                serialized::{PluginSerializedBytes, VersionedSerializable},
+            },
+            plugin_runner::{cache::PLUGIN_MODULE_CACHE, create_plugin_transform_executor},
+        };
+
+        let metadata_context = Arc::new(TransformPluginMetadataContext::new(
+            Some(self.filename.to_string()),
+            self.env_name.clone(),
+            None,
+        ));
+        let mut serialized =
+            PluginSerializedBytes::try_serialize(&VersionedSerializable::new(program.clone()))
+                .map_err(|err| format!("failed to serialize the program for plugins: {:?}", err))?;
+
+        for plugin in &self.plugins {
+            let failed = |err| {
+                format!(
+                    "failed to invoke `{}` as a transform plugin: {:?}",
+                    plugin.path.display(),
+                    err
+                )
+            };
+
+            // Compiled modules are kept in `PLUGIN_MODULE_CACHE`, so each
+            // plugin is only compiled once per process.
+            let mut executor = create_plugin_transform_executor(
+                &plugin.path,
+                &PLUGIN_MODULE_CACHE,
+                &self.cm,
+                &metadata_context,
+                Some(plugin.config.clone()),
+            )
+            .map_err(failed)?;
+            if !executor.is_transform_schema_compatible().map_err(failed)? {
+                return Err(format!(
+                    "`{}` was built for an incompatible version of swc",
+                    plugin.path.display()
+                ));
+            }
+            serialized = executor
+                .transform(&serialized, self.unresolved_mark, false)
+                .map_err(failed)?;
+        }
+
+        serialized
+            .deserialize()
+            .map(|program| program.into_inner())
+            .map_err(|err| format!("failed to deserialize the program from plugins: {:?}", err))
+    }
+
+    #[cfg(not(feature = "plugin"))]
+    fn apply(&self, _program: &Program) -> Result<Program, String> {
+        Err("Transform plugins require next-swc to be built with the `plugin` feature".into())
+    }
+}
@@ -1,4 +1,5 @@
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use swc_core::{
@@ -6,7 +7,7 @@ use swc_core::{
     ecma::visit::{noop_fold_type, Fold, FoldWith},
 };
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Config {
     All(bool),
@@ -22,7 +23,7 @@ impl Config {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 pub struct Options {
     #[serde(default)]
     pub properties: Vec<String>,
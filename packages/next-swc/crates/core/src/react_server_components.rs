@@ -1,4 +1,8 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use once_cell::sync::Lazy;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use swc_core::{
@@ -9,11 +13,21 @@ use swc_core::{
     },
     ecma::ast::*,
     ecma::atoms::{js_word, JsWord},
-    ecma::utils::{prepend_stmts, quote_ident, quote_str, ExprFactory},
-    ecma::visit::{as_folder, noop_visit_mut_type, Fold, VisitMut, VisitMutWith},
+    ecma::utils::{quote_ident, quote_str, ExprFactory},
+    ecma::visit::{
+        as_folder, noop_visit_mut_type, noop_visit_type, Fold, Visit, VisitMut, VisitMutWith,
+        VisitWith,
+    },
 };
 
-#[derive(Clone, Debug, Deserialize)]
+use crate::{
+    app_metadata, async_client_components,
+    manifests::{self, ClientReferenceFragment},
+    metadata::{collect_exports, ModuleMetadata, ServerAction},
+    route_segment_config, serializable_props, server_actions,
+};
+
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Config {
     All(bool),
@@ -27,186 +41,448 @@ impl Config {
             Config::WithOptions(_) => true,
         }
     }
+
+    pub fn with_esm_client_references(self, esm_client_references: bool) -> Self {
+        match self {
+            Config::WithOptions(options) => Config::WithOptions(Options {
+                esm_client_references,
+                ..options
+            }),
+            Config::All(true) => Config::WithOptions(Options {
+                is_server: true,
+                esm_client_references,
+                ..Default::default()
+            }),
+            config => config,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Options {
     pub is_server: bool,
+    /// Modules which can't be imported by Server Components, like
+    /// `client-only`.
+    #[serde(default)]
+    pub invalid_server_imports: DenyList,
+    /// Modules which can't be imported by Client Components, like
+    /// `server-only`.
+    #[serde(default)]
+    pub invalid_client_imports: DenyList,
+    /// APIs which can't be imported by Server Components, keyed by the module
+    /// providing them. `react` and `react-dom` have built-in entries.
+    #[serde(default)]
+    pub invalid_server_apis: BTreeMap<String, DenyList>,
+    /// Known Client Component modules, as import specifiers or absolute
+    /// paths. Server Components can only pass serializable props to the
+    /// components they import from these.
+    #[serde(default)]
+    pub client_modules: Vec<String>,
+    /// Emits the references of client entries as ES module exports instead of
+    /// CommonJS, set from the `module` option of swc.
+    #[serde(skip)]
+    pub esm_client_references: bool,
+    /// Server Action IDs are derived from this path instead of the absolute
+    /// one, so they are the same wherever the project is built.
+    #[serde(default)]
+    pub relative_file_path_from_root: Option<String>,
 }
 
+/// Changes to one of the built-in deny-lists.
+#[derive(Clone, Debug, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DenyList {
+    /// Replaces the built-in entries.
+    #[serde(default)]
+    pub replace: Option<Vec<DenyListEntry>>,
+    /// Added to the built-in or replaced entries.
+    #[serde(default)]
+    pub extend: Vec<DenyListEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DenyListEntry {
+    Name(String),
+    /// Reported with `message` instead of the built-in error.
+    WithMessage {
+        name: String,
+        message: String,
+    },
+}
+
+impl DenyList {
+    pub(crate) fn apply(&self, builtin: &[&str]) -> Vec<Denied> {
+        let mut entries: Vec<Denied> = match &self.replace {
+            Some(entries) => entries.iter().map(Denied::from).collect(),
+            None => builtin
+                .iter()
+                .map(|name| Denied {
+                    name: JsWord::from(*name),
+                    message: None,
+                })
+                .collect(),
+        };
+        entries.extend(self.extend.iter().map(Denied::from));
+        entries
+    }
+}
+
+pub(crate) struct Denied {
+    name: JsWord,
+    message: Option<String>,
+}
+
+impl From<&DenyListEntry> for Denied {
+    fn from(entry: &DenyListEntry) -> Self {
+        match entry {
+            DenyListEntry::Name(name) => Denied {
+                name: name.as_str().into(),
+                message: None,
+            },
+            DenyListEntry::WithMessage { name, message } => Denied {
+                name: name.as_str().into(),
+                message: Some(message.clone()),
+            },
+        }
+    }
+}
+
+pub(crate) fn find_denied<'a>(list: &'a [Denied], name: &JsWord) -> Option<&'a Denied> {
+    list.iter().find(|denied| denied.name == *name)
+}
+
+/// Custom messages use their own code, as they replace the message which
+/// Next.js would show for the built-in code. They start on the line after the
+/// module they were reported for, as they can span multiple lines.
+pub(crate) fn denied_message(code: &str, denied: &Denied, source: &str) -> String {
+    match &denied.message {
+        Some(message) => format!("NEXT_RSC_ERR_CUSTOM: {}\n{}", source, message),
+        None => format!("{}: {}", code, denied.name),
+    }
+}
+
+/// `source` is the module which is denied, or which provides the denied API.
+fn report_denied(span: Span, code: &str, denied: &Denied, source: &str) {
+    HANDLER.with(|handler| {
+        handler
+            .struct_span_err(span, &denied_message(code, denied, source))
+            .emit()
+    })
+}
+
+/// Next.js explains `NEXT_RSC_ERR_REACT_API` in terms of React, so the APIs
+/// denied for other packages are reported with a message naming the package
+/// instead.
+fn report_denied_api(span: Span, denied: &Denied, source: &str) {
+    if denied.message.is_some() || source == "react" || source == "react-dom" {
+        return report_denied(span, "NEXT_RSC_ERR_REACT_API", denied, source);
+    }
+
+    let denied = Denied {
+        name: denied.name.clone(),
+        message: Some(format!(
+            "`{}` from \"{}\" only works in Client Components. Add the \"use client\" directive \
+             at the top of the file to use it.",
+            denied.name, source
+        )),
+    };
+    report_denied(span, "NEXT_RSC_ERR_CUSTOM", &denied, source)
+}
+
+pub(crate) const INVALID_SERVER_IMPORTS: &[&str] = &[
+    "client-only",
+    "react-dom/client",
+    "react-dom/server",
+    "next/router",
+];
+
+pub(crate) const INVALID_CLIENT_IMPORTS: &[&str] = &["server-only", "next/headers"];
+
+const INVALID_SERVER_REACT_APIS: &[&str] = &[
+    "Component",
+    "createContext",
+    "createFactory",
+    "PureComponent",
+    "useDeferredValue",
+    "useEffect",
+    "useImperativeHandle",
+    "useInsertionEffect",
+    "useLayoutEffect",
+    "useReducer",
+    "useRef",
+    "useState",
+    "useSyncExternalStore",
+    "useTransition",
+];
+
+const INVALID_SERVER_REACT_DOM_APIS: &[&str] =
+    &["findDOMNode", "flushSync", "unstable_batchedUpdates"];
+
 struct ReactServerComponents<C: Comments> {
     is_server: bool,
     filepath: String,
     comments: C,
-    invalid_server_imports: Vec<JsWord>,
-    invalid_client_imports: Vec<JsWord>,
-    invalid_server_react_apis: Vec<JsWord>,
-    invalid_server_react_dom_apis: Vec<JsWord>,
+    invalid_server_imports: Vec<Denied>,
+    invalid_client_imports: Vec<Denied>,
+    /// Keyed by the module providing the APIs.
+    invalid_server_apis: Vec<(JsWord, Vec<Denied>)>,
+    esm_client_references: bool,
+    client_modules: Vec<String>,
+    relative_file_path_from_root: Option<String>,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
 }
 
-struct ModuleImports {
-    source: (JsWord, Span),
-    specifiers: Vec<(JsWord, Span)>,
+pub(crate) struct ModuleImports {
+    pub source: (JsWord, Span),
+    pub specifiers: Vec<(JsWord, Span)>,
 }
 
 impl<C: Comments> VisitMut for ReactServerComponents<C> {
     noop_visit_mut_type!();
 
     fn visit_mut_module(&mut self, module: &mut Module) {
-        let (is_client_entry, imports) = self.collect_top_level_directives_and_imports(module);
+        module.visit_with(&mut NestedClientDirectives);
+
+        let (is_client_entry, action_entry, imports) =
+            collect_top_level_directives_and_imports(module);
+
+        if let (true, Some(span)) = (is_client_entry, action_entry) {
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_err(
+                        span,
+                        "\"use client\" and \"use server\" can't be used in the same file.",
+                    )
+                    .emit()
+            })
+        }
+
+        // Client Components only go through the client layer when they are
+        // imported, so the config of layouts and pages is read on the server.
+        if self.is_server && is_layout_or_page(&self.filepath) {
+            let config =
+                route_segment_config::collect_route_segment_config(module, is_client_entry);
+            let static_metadata = if is_client_entry {
+                None
+            } else {
+                app_metadata::collect_static_metadata(module)
+            };
+            if let Some(metadata) = &self.metadata {
+                let mut metadata = metadata.borrow_mut();
+                metadata.route_segment_config = config;
+                metadata.static_metadata = static_metadata;
+            }
+        }
 
         if self.is_server {
             if !is_client_entry {
-                self.assert_server_graph(&imports);
+                self.assert_server_graph(&imports, module);
+                serializable_props::assert_serializable_props(
+                    module,
+                    &self.filepath,
+                    &self.client_modules,
+                    action_entry.is_some(),
+                );
             } else {
+                async_client_components::assert_no_async_client_components(module);
                 self.to_module_ref(module);
                 return;
             }
         } else {
             self.assert_client_graph(&imports, module);
         }
+
+        if action_entry.is_some() && !is_client_entry {
+            let actions = self.to_server_actions(server_actions::collect_file_actions(module));
+            if !self.is_server {
+                let actions: Vec<ServerAction> =
+                    actions.into_iter().map(|(action, _)| action).collect();
+                server_actions::to_action_proxies(module, &actions);
+                return;
+            }
+            self.register_actions(module, &actions);
+        } else if is_client_entry {
+            server_actions::assert_no_function_actions(module);
+        } else {
+            let actions = self.to_server_actions(server_actions::collect_function_actions(module));
+            if self.is_server {
+                self.register_actions(module, &actions);
+            } else {
+                server_actions::to_function_action_proxies(module, &actions);
+            }
+        }
+
         module.visit_mut_children_with(self)
     }
 }
 
 impl<C: Comments> ReactServerComponents<C> {
-    // Collects top level directives and imports, then removes specific ones
-    // from the AST.
-    fn collect_top_level_directives_and_imports(
+    fn to_server_actions(
         &self,
-        module: &mut Module,
-    ) -> (bool, Vec<ModuleImports>) {
-        let mut imports: Vec<ModuleImports> = vec![];
-        let mut finished_directives = false;
-        let mut is_client_entry = false;
-
-        let _ = &module.body.retain(|item| {
-            match item {
-                ModuleItem::Stmt(stmt) => {
-                    if !finished_directives {
-                        if !stmt.is_expr() {
-                            // Not an expression.
-                            finished_directives = true;
-                        }
-
-                        match stmt.as_expr() {
-                            Some(expr_stmt) => {
-                                match &*expr_stmt.expr {
-                                    Expr::Lit(Lit::Str(Str { value, .. })) => {
-                                        if &**value == "use client" {
-                                            is_client_entry = true;
-
-                                            // Remove the directive.
-                                            return false;
-                                        }
-                                    }
-                                    _ => {
-                                        // Other expression types.
-                                        finished_directives = true;
-                                    }
-                                }
-                            }
-                            None => {
-                                // Not an expression.
-                                finished_directives = true;
-                            }
-                        }
-                    }
-                }
-                ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
-                    let source = import.src.value.clone();
-                    let specifiers = import
-                        .specifiers
-                        .iter()
-                        .map(|specifier| match specifier {
-                            ImportSpecifier::Named(named) => match &named.imported {
-                                Some(imported) => match &imported {
-                                    ModuleExportName::Ident(i) => (i.to_id().0, i.span),
-                                    ModuleExportName::Str(s) => (s.value.clone(), s.span),
-                                },
-                                None => (named.local.to_id().0, named.local.span),
-                            },
-                            ImportSpecifier::Default(d) => (js_word!(""), d.span),
-                            ImportSpecifier::Namespace(n) => ("*".into(), n.span),
-                        })
-                        .collect();
-
-                    imports.push(ModuleImports {
-                        source: (source, import.span),
-                        specifiers,
-                    });
+        found: Vec<server_actions::FoundAction>,
+    ) -> Vec<(ServerAction, Ident)> {
+        let actions: Vec<(ServerAction, Ident)> = found
+            .into_iter()
+            .map(|action| {
+                (
+                    ServerAction {
+                        id: server_actions::action_id(
+                            self.relative_file_path_from_root
+                                .as_deref()
+                                .unwrap_or(&self.filepath),
+                            &action.name,
+                        ),
+                        name: action.name.to_string(),
+                    },
+                    action.local,
+                )
+            })
+            .collect();
 
-                    finished_directives = true;
-                }
-                _ => {
-                    finished_directives = true;
-                }
-            }
-            true
-        });
+        if let Some(metadata) = &self.metadata {
+            metadata
+                .borrow_mut()
+                .server_actions
+                .extend(actions.iter().map(|(action, _)| action.clone()));
+        }
+        actions
+    }
 
-        (is_client_entry, imports)
+    // Register the actions of a server module. Their IDs are returned with the
+    // metadata of the module, for the actions manifest.
+    fn register_actions(&self, module: &mut Module, actions: &[(ServerAction, Ident)]) {
+        if !actions.is_empty() {
+            server_actions::register_actions(module, actions);
+        }
     }
 
     // Convert the client module to the module reference code and add a special
-    // comment to the top of the file.
+    // comment with its exports to the top of the file.
     fn to_module_ref(&self, module: &mut Module) {
+        let mut exports = vec![];
+        let mut star_exports = vec![];
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(decl) = item {
+                collect_exports(decl, &mut exports, &mut star_exports);
+            }
+        }
+        // The exports of CommonJS modules and of `export *` are only known at
+        // runtime, so those modules are referenced as a whole.
+        if !star_exports.is_empty() {
+            exports.clear();
+        }
+        let chunking = manifests::chunking_hints(module, &self.filepath);
+        let is_async = chunking.is_async;
+
+        if let Some(metadata) = &self.metadata {
+            metadata.borrow_mut().client_reference = Some(ClientReferenceFragment {
+                module_path: self.filepath.clone(),
+                exports: if exports.is_empty() {
+                    vec!["*".to_string()]
+                } else {
+                    exports.clone()
+                },
+                chunking,
+            });
+        }
+
         // Clear all the statements and module declarations.
         module.body.clear();
 
-        let proxy_ident = quote_ident!("createProxy");
-        let filepath = quote_str!(&*self.filepath);
+        if exports.is_empty() {
+            let proxy_ident = quote_ident!("createProxy");
+            let filepath = quote_str!(&*self.filepath);
 
-        prepend_stmts(
-            &mut module.body,
-            vec![
-                ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+            module.body.push(require_mod_ref_proxy(proxy_ident.clone()));
+            module
+                .body
+                .push(assign_module_exports(Box::new(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: proxy_ident.as_callee(),
+                    args: reference_args(vec![filepath.as_arg()], is_async),
+                    type_args: Default::default(),
+                }))));
+        } else if self.esm_client_references {
+            module
+                .body
+                .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
                     span: DUMMY_SP,
-                    kind: VarDeclKind::Const,
-                    decls: vec![VarDeclarator {
+                    specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
                         span: DUMMY_SP,
-                        name: Pat::Object(ObjectPat {
-                            span: DUMMY_SP,
-                            props: vec![ObjectPatProp::Assign(AssignPatProp {
-                                span: DUMMY_SP,
-                                key: proxy_ident,
-                                value: None,
-                            })],
-                            optional: false,
-                            type_ann: None,
-                        }),
-                        init: Some(Box::new(Expr::Call(CallExpr {
+                        local: quote_ident!("createReference"),
+                        imported: None,
+                        is_type_only: false,
+                    })],
+                    src: Box::new(quote_str!("private-next-rsc-mod-ref-proxy")),
+                    type_only: false,
+                    asserts: None,
+                })));
+
+            let mut specifiers = vec![];
+            for (i, name) in exports.iter().enumerate() {
+                let local = quote_ident!(format!("$$REF_{}", i));
+                module
+                    .body
+                    .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                        span: DUMMY_SP,
+                        kind: VarDeclKind::Const,
+                        decls: vec![VarDeclarator {
                             span: DUMMY_SP,
-                            callee: quote_ident!("require").as_callee(),
-                            args: vec![quote_str!("private-next-rsc-mod-ref-proxy").as_arg()],
-                            type_args: Default::default(),
-                        }))),
-                        definite: false,
-                    }],
-                    declare: false,
-                })))),
-                ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+                            name: Pat::Ident(local.clone().into()),
+                            init: Some(self.create_reference(name, is_async)),
+                            definite: false,
+                        }],
+                        declare: false,
+                    })))));
+                specifiers.push(ExportSpecifier::Named(ExportNamedSpecifier {
                     span: DUMMY_SP,
-                    expr: Box::new(Expr::Assign(AssignExpr {
+                    orig: ModuleExportName::Ident(local),
+                    exported: Some(module_export_name(name)),
+                    is_type_only: false,
+                }));
+            }
+            module
+                .body
+                .push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+                    NamedExport {
                         span: DUMMY_SP,
-                        left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
-                            span: DUMMY_SP,
-                            obj: Box::new(Expr::Ident(quote_ident!("module"))),
-                            prop: MemberProp::Ident(quote_ident!("exports")),
-                        }))),
-                        op: op!("="),
-                        right: Box::new(Expr::Call(CallExpr {
-                            span: DUMMY_SP,
-                            callee: quote_ident!("createProxy").as_callee(),
-                            args: vec![filepath.as_arg()],
-                            type_args: Default::default(),
-                        })),
-                    })),
-                })),
-            ]
-            .into_iter(),
-        );
+                        specifiers,
+                        src: None,
+                        type_only: false,
+                        asserts: None,
+                    },
+                )));
+        } else {
+            // `{ __esModule: true, default: createReference(filepath, "default") }`
+            let mut props = vec![PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                key: PropName::Ident(quote_ident!("__esModule")),
+                value: Box::new(Expr::Lit(Lit::Bool(Bool {
+                    span: DUMMY_SP,
+                    value: true,
+                }))),
+            })))];
+            props.extend(exports.iter().map(|name| {
+                PropOrSpread::Prop(Box::new(Prop::KeyValue(KeyValueProp {
+                    key: match module_export_name(name) {
+                        ModuleExportName::Ident(ident) => PropName::Ident(ident),
+                        ModuleExportName::Str(s) => PropName::Str(s),
+                    },
+                    value: self.create_reference(name, is_async),
+                })))
+            }));
+
+            module
+                .body
+                .push(require_mod_ref_proxy(quote_ident!("createReference")));
+            module
+                .body
+                .push(assign_module_exports(Box::new(Expr::Object(ObjectLit {
+                    span: DUMMY_SP,
+                    props,
+                }))));
+        }
 
         // Prepend a special comment to the top of the file.
         self.comments.add_leading(
@@ -214,75 +490,106 @@ impl<C: Comments> ReactServerComponents<C> {
             Comment {
                 span: DUMMY_SP,
                 kind: CommentKind::Block,
-                text: " __next_internal_client_entry_do_not_use__ ".into(),
+                text: if exports.is_empty() {
+                    " __next_internal_client_entry_do_not_use__ ".into()
+                } else {
+                    // The names are listed as JSON, which can hold any export
+                    // name. `*/` is escaped so that no name ends the comment.
+                    format!(
+                        " __next_internal_client_entry_do_not_use__ {} ",
+                        serde_json::to_string(&exports)
+                            .unwrap()
+                            .replace("*/", "*\\/")
+                    )
+                    .into()
+                },
             },
         );
     }
 
-    fn assert_server_graph(&self, imports: &Vec<ModuleImports>) {
+    /// `createReference(filepath, name)`, the reference to a single export.
+    fn create_reference(&self, name: &str, is_async: bool) -> Box<Expr> {
+        Box::new(Expr::Call(CallExpr {
+            span: DUMMY_SP,
+            callee: quote_ident!("createReference").as_callee(),
+            args: reference_args(
+                vec![
+                    quote_str!(&*self.filepath).as_arg(),
+                    quote_str!(name).as_arg(),
+                ],
+                is_async,
+            ),
+            type_args: Default::default(),
+        }))
+    }
+
+    fn assert_server_graph(&self, imports: &Vec<ModuleImports>, module: &Module) {
         for import in imports {
-            let source = import.source.0.clone();
-            if self.invalid_server_imports.contains(&source) {
-                HANDLER.with(|handler| {
-                    handler
-                        .struct_span_err(
-                            import.source.1,
-                            format!("NEXT_RSC_ERR_SERVER_IMPORT: {}", source).as_str(),
-                        )
-                        .emit()
-                })
+            let source = &import.source.0;
+            if let Some(denied) = find_denied(&self.invalid_server_imports, source) {
+                report_denied(
+                    import.source.1,
+                    "NEXT_RSC_ERR_SERVER_IMPORT",
+                    denied,
+                    source,
+                );
             }
-            if source == *"react" {
+            if let Some((_, apis)) = self
+                .invalid_server_apis
+                .iter()
+                .find(|(module, _)| module == source)
+            {
                 for specifier in &import.specifiers {
-                    if self.invalid_server_react_apis.contains(&specifier.0) {
-                        HANDLER.with(|handler| {
-                            handler
-                                .struct_span_err(
-                                    specifier.1,
-                                    format!("NEXT_RSC_ERR_REACT_API: {}", &specifier.0).as_str(),
-                                )
-                                .emit()
-                        })
+                    if let Some(denied) = find_denied(apis, &specifier.0) {
+                        report_denied_api(specifier.1, denied, source);
                     }
                 }
             }
-            if source == *"react-dom" {
+        }
+
+        // `React.useState()` with `import React from 'react'` or
+        // `import * as React from 'react'`.
+        let mut namespaces = vec![];
+        for item in &module.body {
+            if let ModuleItem::ModuleDecl(ModuleDecl::Import(import)) = item {
+                let apis = match self
+                    .invalid_server_apis
+                    .iter()
+                    .find(|(module, _)| *module == import.src.value)
+                {
+                    Some((_, apis)) => apis,
+                    None => continue,
+                };
                 for specifier in &import.specifiers {
-                    if self.invalid_server_react_dom_apis.contains(&specifier.0) {
-                        HANDLER.with(|handler| {
-                            handler
-                                .struct_span_err(
-                                    specifier.1,
-                                    format!("NEXT_RSC_ERR_REACT_API: {}", &specifier.0).as_str(),
-                                )
-                                .emit()
-                        })
+                    match specifier {
+                        ImportSpecifier::Default(ImportDefaultSpecifier { local, .. })
+                        | ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => {
+                            namespaces.push((local.to_id(), &*import.src.value, apis));
+                        }
+                        ImportSpecifier::Named(..) => {}
                     }
                 }
             }
         }
+        if !namespaces.is_empty() {
+            module.visit_with(&mut NamespaceApiUses { namespaces });
+        }
     }
 
     fn assert_client_graph(&self, imports: &Vec<ModuleImports>, module: &Module) {
         for import in imports {
-            let source = import.source.0.clone();
-            if self.invalid_client_imports.contains(&source) {
-                HANDLER.with(|handler| {
-                    handler
-                        .struct_span_err(
-                            import.source.1,
-                            format!("NEXT_RSC_ERR_CLIENT_IMPORT: {}", source).as_str(),
-                        )
-                        .emit()
-                })
+            if let Some(denied) = find_denied(&self.invalid_client_imports, &import.source.0) {
+                report_denied(
+                    import.source.1,
+                    "NEXT_RSC_ERR_CLIENT_IMPORT",
+                    denied,
+                    &import.source.0,
+                );
             }
         }
 
         // Assert `getServerSideProps` and `getStaticProps` exports.
-        let is_layout_or_page = Regex::new(r"/(page|layout)\.(ts|js)x?$")
-            .unwrap()
-            .is_match(&self.filepath);
-        if is_layout_or_page {
+        if is_layout_or_page(&self.filepath) {
             let mut span = DUMMY_SP;
             let mut has_get_server_side_props = false;
             let mut has_get_static_props = false;
@@ -374,7 +681,353 @@ impl<C: Comments> ReactServerComponents<C> {
                         .emit()
                 })
             }
+
+            for (name, span) in app_metadata::find_metadata_exports(module) {
+                HANDLER.with(|handler| {
+                    handler
+                        .struct_span_err(
+                            span,
+                            &format!("`{}` is not allowed in Client Components.", name),
+                        )
+                        .emit()
+                })
+            }
+        }
+    }
+}
+
+/// Names which are not identifiers, like `export { a as "b-c" }`, are kept
+/// as strings.
+pub(crate) fn module_export_name(name: &str) -> ModuleExportName {
+    let mut chars = name.chars();
+    let is_ident = matches!(chars.next(), Some(c) if c == '$' || c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '$' || c == '_' || c.is_alphanumeric());
+
+    if is_ident {
+        ModuleExportName::Ident(quote_ident!(name))
+    } else {
+        ModuleExportName::Str(quote_str!(name))
+    }
+}
+
+/// `const { name } = require("private-next-rsc-mod-ref-proxy")`
+fn require_mod_ref_proxy(name: Ident) -> ModuleItem {
+    ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Object(ObjectPat {
+                span: DUMMY_SP,
+                props: vec![ObjectPatProp::Assign(AssignPatProp {
+                    span: DUMMY_SP,
+                    key: name,
+                    value: None,
+                })],
+                optional: false,
+                type_ann: None,
+            }),
+            init: Some(Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: quote_ident!("require").as_callee(),
+                args: vec![quote_str!("private-next-rsc-mod-ref-proxy").as_arg()],
+                type_args: Default::default(),
+            }))),
+            definite: false,
+        }],
+        declare: false,
+    }))))
+}
+
+/// Marks the references of modules with top-level `await`, whose exports are
+/// only available once the module has been evaluated.
+fn reference_args(mut args: Vec<ExprOrSpread>, is_async: bool) -> Vec<ExprOrSpread> {
+    if is_async {
+        args.push(
+            Expr::Lit(Lit::Bool(Bool {
+                span: DUMMY_SP,
+                value: true,
+            }))
+            .as_arg(),
+        );
+    }
+    args
+}
+
+fn assign_module_exports(value: Box<Expr>) -> ModuleItem {
+    ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+        span: DUMMY_SP,
+        expr: Box::new(Expr::Assign(AssignExpr {
+            span: DUMMY_SP,
+            left: PatOrExpr::Expr(Box::new(Expr::Member(MemberExpr {
+                span: DUMMY_SP,
+                obj: Box::new(Expr::Ident(quote_ident!("module"))),
+                prop: MemberProp::Ident(quote_ident!("exports")),
+            }))),
+            op: op!("="),
+            right: value,
+        })),
+    }))
+}
+
+fn is_layout_or_page(filepath: &str) -> bool {
+    static LAYOUT_OR_PAGE_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"/(page|layout)\.(ts|js)x?$").unwrap());
+
+    LAYOUT_OR_PAGE_REGEX.is_match(filepath)
+}
+
+// Collects top level directives and imports, then removes specific ones
+// from the AST.
+pub(crate) fn collect_top_level_directives_and_imports(
+    module: &mut Module,
+) -> (bool, Option<Span>, Vec<ModuleImports>) {
+    let mut imports: Vec<ModuleImports> = vec![];
+    let mut finished_directives = false;
+    let mut is_client_entry = false;
+    let mut action_entry = None;
+
+    let _ = &module.body.retain(|item| {
+        match item {
+            ModuleItem::Stmt(stmt) => {
+                if let Some((value, span)) = directive(stmt) {
+                    if finished_directives && &**value == "use client" {
+                        HANDLER.with(|handler| {
+                            handler
+                                .struct_span_err(
+                                    span,
+                                    "The \"use client\" directive must be placed before imports \
+                                     and other code. Move it to the top of the file.",
+                                )
+                                .emit()
+                        })
+                    } else {
+                        assert_not_malformed_directive(value, span);
+                    }
+                }
+
+                if !finished_directives {
+                    if !stmt.is_expr() {
+                        // Not an expression.
+                        finished_directives = true;
+                    }
+
+                    match stmt.as_expr() {
+                        Some(expr_stmt) => {
+                            match &*expr_stmt.expr {
+                                Expr::Lit(Lit::Str(Str { value, .. })) => {
+                                    if &**value == "use client" {
+                                        if is_client_entry {
+                                            HANDLER.with(|handler| {
+                                                handler
+                                                    .struct_span_warn(
+                                                        expr_stmt.span,
+                                                        "The \"use client\" directive is \
+                                                         repeated. Only the first one is needed.",
+                                                    )
+                                                    .emit()
+                                            })
+                                        }
+                                        is_client_entry = true;
+
+                                        // Remove the directive.
+                                        return false;
+                                    }
+                                    if &**value == "use server" {
+                                        action_entry = Some(expr_stmt.span);
+
+                                        // Remove the directive.
+                                        return false;
+                                    }
+                                }
+                                _ => {
+                                    // Other expression types.
+                                    finished_directives = true;
+                                }
+                            }
+                        }
+                        None => {
+                            // Not an expression.
+                            finished_directives = true;
+                        }
+                    }
+                }
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import)) => {
+                let source = import.src.value.clone();
+                let specifiers = import
+                    .specifiers
+                    .iter()
+                    .map(|specifier| match specifier {
+                        ImportSpecifier::Named(named) => match &named.imported {
+                            Some(imported) => match &imported {
+                                ModuleExportName::Ident(i) => (i.to_id().0, i.span),
+                                ModuleExportName::Str(s) => (s.value.clone(), s.span),
+                            },
+                            None => (named.local.to_id().0, named.local.span),
+                        },
+                        ImportSpecifier::Default(d) => (js_word!(""), d.span),
+                        ImportSpecifier::Namespace(n) => ("*".into(), n.span),
+                    })
+                    .collect();
+
+                imports.push(ModuleImports {
+                    source: (source, import.span),
+                    specifiers,
+                });
+
+                finished_directives = true;
+            }
+            _ => {
+                finished_directives = true;
+            }
+        }
+        true
+    });
+
+    (is_client_entry, action_entry, imports)
+}
+
+/// The value of a string expression statement, which is a directive when it
+/// is at the start of a file or function.
+fn directive(stmt: &Stmt) -> Option<(&JsWord, Span)> {
+    match stmt {
+        Stmt::Expr(ExprStmt {
+            expr: box Expr::Lit(Lit::Str(Str { value, .. })),
+            span,
+        }) => Some((value, *span)),
+        _ => None,
+    }
+}
+
+/// Catches spellings like "use-client" or "Use Client", which would be
+/// ignored and leave the file a Server Component.
+fn assert_not_malformed_directive(value: &str, span: Span) {
+    let normalized: String = value
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    if normalized == "useclient" && value != "use client" {
+        HANDLER.with(|handler| {
+            handler
+                .struct_span_err(
+                    span,
+                    &format!(
+                        "\"{}\" is not a valid directive. Did you mean \"use client\"?",
+                        value
+                    ),
+                )
+                .emit()
+        })
+    }
+}
+
+/// Reports "use client" directives in functions, where they have no effect.
+struct NestedClientDirectives;
+
+impl NestedClientDirectives {
+    fn check_body(&self, stmts: &[Stmt]) {
+        for (value, span) in stmts.iter().map_while(directive) {
+            if &**value == "use client" {
+                HANDLER.with(|handler| {
+                    handler
+                        .struct_span_err(
+                            span,
+                            "The \"use client\" directive has no effect inside a function. Move \
+                             it to the top of the file to make it a Client Component.",
+                        )
+                        .emit()
+                })
+            } else {
+                assert_not_malformed_directive(value, span);
+            }
+        }
+    }
+}
+
+impl Visit for NestedClientDirectives {
+    noop_visit_type!();
+
+    fn visit_function(&mut self, function: &Function) {
+        if let Some(body) = &function.body {
+            self.check_body(&body.stmts);
+        }
+        function.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+        if let BlockStmtOrExpr::BlockStmt(body) = &arrow.body {
+            self.check_body(&body.stmts);
+        }
+        arrow.visit_children_with(self);
+    }
+}
+
+/// Uses of denied APIs through the default or namespace import of the module
+/// providing them.
+struct NamespaceApiUses<'a> {
+    /// The binding, the module it was imported from and the denied APIs of
+    /// the module.
+    namespaces: Vec<(Id, &'a str, &'a Vec<Denied>)>,
+}
+
+impl<'a> NamespaceApiUses<'a> {
+    fn apis(&self, ident: &Ident) -> Option<(&'a str, &'a Vec<Denied>)> {
+        let id = ident.to_id();
+        self.namespaces
+            .iter()
+            .find(|(namespace, ..)| *namespace == id)
+            .map(|(_, source, apis)| (*source, *apis))
+    }
+
+    fn check(&self, (source, apis): (&str, &Vec<Denied>), name: &JsWord, span: Span) {
+        if let Some(denied) = find_denied(apis, name) {
+            report_denied_api(span, denied, source);
+        }
+    }
+}
+
+impl Visit for NamespaceApiUses<'_> {
+    noop_visit_type!();
+
+    fn visit_member_expr(&mut self, expr: &MemberExpr) {
+        if let Expr::Ident(obj) = &*expr.obj {
+            if let Some(apis) = self.apis(obj) {
+                match &expr.prop {
+                    MemberProp::Ident(prop) => self.check(apis, &prop.sym, expr.span),
+                    MemberProp::Computed(ComputedPropName {
+                        expr: box Expr::Lit(Lit::Str(prop)),
+                        ..
+                    }) => self.check(apis, &prop.value, expr.span),
+                    _ => {}
+                }
+            }
+        }
+        expr.visit_children_with(self);
+    }
+
+    // `const { useState } = React`
+    fn visit_var_declarator(&mut self, decl: &VarDeclarator) {
+        if let (Pat::Object(ObjectPat { props, .. }), Some(box Expr::Ident(init))) =
+            (&decl.name, &decl.init)
+        {
+            if let Some(apis) = self.apis(init) {
+                for prop in props {
+                    match prop {
+                        ObjectPatProp::KeyValue(KeyValuePatProp {
+                            key: PropName::Ident(key),
+                            ..
+                        }) => self.check(apis, &key.sym, key.span),
+                        ObjectPatProp::Assign(AssignPatProp { key, .. }) => {
+                            self.check(apis, &key.sym, key.span)
+                        }
+                        _ => {}
+                    }
+                }
+            }
         }
+        decl.visit_children_with(self);
     }
 }
 
@@ -382,42 +1035,52 @@ pub fn server_components<C: Comments>(
     filename: FileName,
     config: Config,
     comments: C,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
 ) -> impl Fold + VisitMut {
-    let is_server: bool = match config {
-        Config::WithOptions(x) => x.is_server,
-        _ => true,
+    let options = match config {
+        Config::WithOptions(options) => options,
+        _ => Options {
+            is_server: true,
+            ..Default::default()
+        },
     };
+
+    let mut invalid_server_apis: Vec<(JsWord, Vec<Denied>)> = vec![
+        (
+            "react".into(),
+            DenyList::default().apply(INVALID_SERVER_REACT_APIS),
+        ),
+        (
+            "react-dom".into(),
+            DenyList::default().apply(INVALID_SERVER_REACT_DOM_APIS),
+        ),
+    ];
+    for (module, list) in &options.invalid_server_apis {
+        let builtin = match &**module {
+            "react" => INVALID_SERVER_REACT_APIS,
+            "react-dom" => INVALID_SERVER_REACT_DOM_APIS,
+            _ => &[],
+        };
+        let apis = list.apply(builtin);
+        match invalid_server_apis
+            .iter_mut()
+            .find(|(existing, _)| &**existing == module.as_str())
+        {
+            Some((_, existing)) => *existing = apis,
+            None => invalid_server_apis.push((module.as_str().into(), apis)),
+        }
+    }
+
     as_folder(ReactServerComponents {
-        is_server,
+        is_server: options.is_server,
         comments,
         filepath: filename.to_string(),
-        invalid_server_imports: vec![
-            JsWord::from("client-only"),
-            JsWord::from("react-dom/client"),
-            JsWord::from("react-dom/server"),
-            JsWord::from("next/router"),
-        ],
-        invalid_client_imports: vec![JsWord::from("server-only"), JsWord::from("next/headers")],
-        invalid_server_react_dom_apis: vec![
-            JsWord::from("findDOMNode"),
-            JsWord::from("flushSync"),
-            JsWord::from("unstable_batchedUpdates"),
-        ],
-        invalid_server_react_apis: vec![
-            JsWord::from("Component"),
-            JsWord::from("createContext"),
-            JsWord::from("createFactory"),
-            JsWord::from("PureComponent"),
-            JsWord::from("useDeferredValue"),
-            JsWord::from("useEffect"),
-            JsWord::from("useImperativeHandle"),
-            JsWord::from("useInsertionEffect"),
-            JsWord::from("useLayoutEffect"),
-            JsWord::from("useReducer"),
-            JsWord::from("useRef"),
-            JsWord::from("useState"),
-            JsWord::from("useSyncExternalStore"),
-            JsWord::from("useTransition"),
-        ],
+        invalid_server_imports: options.invalid_server_imports.apply(INVALID_SERVER_IMPORTS),
+        invalid_client_imports: options.invalid_client_imports.apply(INVALID_CLIENT_IMPORTS),
+        invalid_server_apis,
+        esm_client_references: options.esm_client_references,
+        client_modules: options.client_modules,
+        relative_file_path_from_root: options.relative_file_path_from_root,
+        metadata,
     })
 }
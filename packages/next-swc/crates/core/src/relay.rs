@@ -1,5 +1,6 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use std::path::{Path, PathBuf};
 
@@ -11,7 +12,7 @@ use swc_core::{
     ecma::visit::{Fold, FoldWith},
 };
 
-#[derive(Copy, Clone, Debug, Deserialize)]
+#[derive(Copy, Clone, Debug, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum RelayLanguageConfig {
     TypeScript,
@@ -31,7 +32,7 @@ struct Relay<'a> {
     config: &'a Config,
 }
 
-#[derive(Deserialize, Debug, Default, Clone)]
+#[derive(Deserialize, Debug, Default, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Config {
     pub src: PathBuf,
@@ -138,7 +139,19 @@ impl<'a> Relay<'a> {
         let operation_name = pull_first_operation_name_from_tpl(tpl);
 
         match operation_name {
-            None => None,
+            None => {
+                // The tag is left in place and throws at runtime.
+                HANDLER.with(|handler| {
+                    handler
+                        .struct_span_warn(
+                            tpl.span,
+                            "No GraphQL operation or fragment was found in this `graphql` \
+                             template, so it was not transformed to a Relay import.",
+                        )
+                        .emit()
+                });
+                None
+            }
             Some(operation_name) => match self.build_require_path(operation_name.as_str()) {
                 Ok(final_path) => Some(build_require_expr_from_path(final_path.to_str().unwrap())),
                 Err(err) => {
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use swc_core::{
@@ -9,7 +10,7 @@ use swc_core::{
 
 use crate::top_level_binding_collector::collect_top_level_decls;
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
 pub enum Config {
     All(bool),
@@ -25,9 +26,10 @@ impl Config {
     }
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 pub struct Options {
     #[serde(default)]
+    #[schemars(with = "Vec<String>")]
     pub exclude: Vec<JsWord>,
 }
 
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use swc_core::{
+    common::{errors::HANDLER, Span, Spanned},
+    ecma::{ast::*, atoms::JsWord},
+};
+
+use crate::metadata::RouteSegmentConfig;
+
+const DYNAMIC: &[&str] = &["auto", "force-dynamic", "error", "force-static"];
+const FETCH_CACHE: &[&str] = &[
+    "auto",
+    "default-cache",
+    "only-cache",
+    "force-cache",
+    "force-no-store",
+    "default-no-store",
+    "only-no-store",
+];
+const RUNTIME: &[&str] = &["nodejs", "edge", "experimental-edge"];
+
+const CONFIG_NAMES: &[&str] = &[
+    "revalidate",
+    "dynamic",
+    "dynamicParams",
+    "fetchCache",
+    "runtime",
+    "preferredRegion",
+    "generateStaticParams",
+];
+
+/// Reads the route segment config exported by an `app` layout or page. Only
+/// literal values are known at build time, so the others are left out and
+/// read by Next.js at runtime. Client Components can't export any of them.
+pub(crate) fn collect_route_segment_config(
+    module: &Module,
+    is_client_entry: bool,
+) -> Option<RouteSegmentConfig> {
+    let mut config = RouteSegmentConfig::default();
+    let bindings = collect_local_bindings(module);
+
+    for item in &module.body {
+        let decl = match item {
+            ModuleItem::ModuleDecl(decl) => decl,
+            _ => continue,
+        };
+
+        match decl {
+            ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(FnDecl { ident, .. }),
+                ..
+            }) if &*ident.sym == "generateStaticParams" => {
+                if is_client_entry {
+                    report_client_export(ident.span, &ident.sym);
+                } else {
+                    config.generate_static_params = true;
+                }
+            }
+            ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            }) => {
+                for decl in &var.decls {
+                    let ident = match &decl.name {
+                        Pat::Ident(BindingIdent { id, .. }) if CONFIG_NAMES.contains(&&*id.sym) => {
+                            id
+                        }
+                        _ => continue,
+                    };
+                    if is_client_entry {
+                        report_client_export(ident.span, &ident.sym);
+                        continue;
+                    }
+
+                    match &decl.init {
+                        Some(init) => read_value(&mut config, &ident.sym, init),
+                        None => report_invalid(ident.span, &ident.sym),
+                    }
+                }
+            }
+            ModuleDecl::ExportNamed(NamedExport {
+                specifiers,
+                src: None,
+                ..
+            }) => {
+                for specifier in specifiers {
+                    if let ExportSpecifier::Named(ExportNamedSpecifier {
+                        span,
+                        orig,
+                        exported,
+                        ..
+                    }) = specifier
+                    {
+                        let name = match exported.as_ref().unwrap_or(orig) {
+                            ModuleExportName::Ident(ident) => &ident.sym,
+                            ModuleExportName::Str(s) => &s.value,
+                        };
+                        if !CONFIG_NAMES.contains(&&**name) {
+                            continue;
+                        }
+                        if is_client_entry {
+                            report_client_export(*span, name);
+                            continue;
+                        }
+
+                        let local = match orig {
+                            ModuleExportName::Ident(ident) => &ident.sym,
+                            ModuleExportName::Str(..) => continue,
+                        };
+                        match bindings.get(local) {
+                            Some(LocalBinding::Const(init)) => read_value(&mut config, name, init),
+                            Some(LocalBinding::Fn) if &**name == "generateStaticParams" => {
+                                config.generate_static_params = true
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if is_client_entry {
+        None
+    } else {
+        Some(config)
+    }
+}
+
+enum LocalBinding<'a> {
+    Const(&'a Expr),
+    Fn,
+}
+
+/// The top level `const` declarations and functions, which `export { name }`
+/// can refer to. `let` and `var` bindings may be reassigned, so they are
+/// left out.
+fn collect_local_bindings(module: &Module) -> HashMap<&JsWord, LocalBinding> {
+    let mut bindings = HashMap::new();
+
+    for item in &module.body {
+        match item {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            })) if var.kind == VarDeclKind::Const => {
+                for decl in &var.decls {
+                    if let (Pat::Ident(BindingIdent { id, .. }), Some(init)) =
+                        (&decl.name, &decl.init)
+                    {
+                        bindings.insert(&id.sym, LocalBinding::Const(init));
+                    }
+                }
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(FnDecl { ident, .. })))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(FnDecl { ident, .. }),
+                ..
+            })) => {
+                bindings.insert(&ident.sym, LocalBinding::Fn);
+            }
+            _ => {}
+        }
+    }
+
+    bindings
+}
+
+/// Whether `expr` is known at build time, like `'edge'`, `-1` or
+/// `['iad1', 'sfo1']`, unlike `60 * 60` or `process.env.RUNTIME`.
+fn is_literal(expr: &Expr) -> bool {
+    match unwrap_expr(expr) {
+        Expr::Lit(..) => true,
+        Expr::Unary(UnaryExpr {
+            op: op!(unary, "-"),
+            arg,
+            ..
+        }) => matches!(unwrap_expr(arg), Expr::Lit(Lit::Num(..))),
+        Expr::Array(ArrayLit { elems, .. }) => elems.iter().all(|elem| match elem {
+            Some(ExprOrSpread { spread: None, expr }) => is_literal(expr),
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+fn read_value(config: &mut RouteSegmentConfig, name: &str, init: &Expr) {
+    let init = unwrap_expr(init);
+    let span = init.span();
+
+    if name == "generateStaticParams" {
+        match init {
+            Expr::Fn(..) | Expr::Arrow(..) => config.generate_static_params = true,
+            _ if is_literal(init) => {
+                report_error(span, "`generateStaticParams` has to be a function.")
+            }
+            _ => {}
+        }
+        return;
+    }
+    if !is_literal(init) {
+        return;
+    }
+
+    match name {
+        "revalidate" => match init {
+            Expr::Lit(Lit::Bool(Bool { value: false, .. })) => {
+                config.revalidate = Some(Value::Bool(false));
+            }
+            Expr::Lit(Lit::Num(Number { value, .. })) if *value >= 0.0 => {
+                config.revalidate = Some(if value.fract() == 0.0 {
+                    Value::from(*value as u64)
+                } else {
+                    Value::from(*value)
+                });
+            }
+            _ => report_error(
+                span,
+                "Invalid value for the `revalidate` route segment config. Expected `false` or a \
+                 number of seconds greater than or equal to 0.",
+            ),
+        },
+        "dynamic" => config.dynamic = read_one_of(name, init, DYNAMIC),
+        "dynamicParams" => match init {
+            Expr::Lit(Lit::Bool(Bool { value, .. })) => config.dynamic_params = Some(*value),
+            _ => report_error(
+                span,
+                "Invalid value for the `dynamicParams` route segment config. Expected `true` or \
+                 `false`.",
+            ),
+        },
+        "fetchCache" => config.fetch_cache = read_one_of(name, init, FETCH_CACHE),
+        "runtime" => config.runtime = read_one_of(name, init, RUNTIME),
+        "preferredRegion" => {
+            let regions = match init {
+                Expr::Lit(Lit::Str(s)) => Some(Value::from(&*s.value)),
+                Expr::Array(ArrayLit { elems, .. }) => elems
+                    .iter()
+                    .map(|elem| match elem {
+                        Some(ExprOrSpread { spread: None, expr }) => match unwrap_expr(expr) {
+                            Expr::Lit(Lit::Str(s)) => Some(Value::from(&*s.value)),
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(Value::Array),
+                _ => None,
+            };
+            if regions.is_none() {
+                report_error(
+                    span,
+                    "Invalid value for the `preferredRegion` route segment config. Expected a \
+                     string or an array of strings.",
+                );
+            }
+            config.preferred_region = regions;
+        }
+        _ => {}
+    }
+}
+
+fn read_one_of(name: &str, init: &Expr, allowed: &[&str]) -> Option<String> {
+    match init {
+        Expr::Lit(Lit::Str(s)) if allowed.contains(&&*s.value) => Some(s.value.to_string()),
+        _ => {
+            report_error(
+                init.span(),
+                &format!(
+                    "Invalid value for the `{}` route segment config. Expected one of {}.",
+                    name,
+                    allowed
+                        .iter()
+                        .map(|value| format!("\"{}\"", value))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+            None
+        }
+    }
+}
+
+/// `'force-static' as const` is as static as the literal.
+pub(crate) fn unwrap_expr(expr: &Expr) -> &Expr {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. })
+        | Expr::TsAs(TsAsExpr { expr, .. })
+        | Expr::TsConstAssertion(TsConstAssertion { expr, .. })
+        | Expr::TsTypeAssertion(TsTypeAssertion { expr, .. }) => unwrap_expr(expr),
+        _ => expr,
+    }
+}
+
+fn report_invalid(span: Span, name: &str) {
+    report_error(
+        span,
+        &format!("The `{}` route segment config has to be initialized.", name),
+    )
+}
+
+fn report_client_export(span: Span, name: &str) {
+    report_error(
+        span,
+        &format!(
+            "The `{}` route segment config can't be exported from a Client Component. Move it to \
+             a layout or page without \"use client\".",
+            name
+        ),
+    )
+}
+
+fn report_error(span: Span, message: &str) {
+    HANDLER.with(|handler| handler.struct_span_err(span, message).emit())
+}
@@ -0,0 +1,253 @@
+use std::path::{Component, Path, PathBuf};
+
+use fxhash::FxHashSet;
+use swc_core::{
+    common::{errors::HANDLER, Span},
+    ecma::ast::*,
+    ecma::visit::{noop_visit_type, Visit, VisitWith},
+};
+
+use crate::server_actions::{arrow_has_directive, function_has_directive};
+
+/// Reports props of Client Components rendered by a Server Component which
+/// can't be serialized, like functions and class instances. Server Actions
+/// are references, so they can be passed.
+pub(crate) fn assert_serializable_props(
+    module: &Module,
+    filepath: &str,
+    client_modules: &[String],
+    is_action_entry: bool,
+) {
+    let mut checker = PropsChecker {
+        client_components: Default::default(),
+        client_namespaces: Default::default(),
+        functions: Default::default(),
+        classes: Default::default(),
+    };
+
+    for item in &module.body {
+        match item {
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import))
+                if !import.type_only
+                    && is_client_module(filepath, &import.src.value, client_modules) =>
+            {
+                for specifier in &import.specifiers {
+                    match specifier {
+                        ImportSpecifier::Named(ImportNamedSpecifier { local, .. })
+                        | ImportSpecifier::Default(ImportDefaultSpecifier { local, .. }) => {
+                            checker.client_components.insert(local.to_id());
+                        }
+                        ImportSpecifier::Namespace(ImportStarAsSpecifier { local, .. }) => {
+                            checker.client_namespaces.insert(local.to_id());
+                        }
+                    }
+                }
+            }
+            ModuleItem::Stmt(Stmt::Decl(decl))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => match decl
+            {
+                Decl::Fn(f) if !is_action(&f.function, is_action_entry) => {
+                    checker.functions.insert(f.ident.to_id());
+                }
+                Decl::Class(c) => {
+                    checker.classes.insert(c.ident.to_id());
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    if !checker.client_components.is_empty() || !checker.client_namespaces.is_empty() {
+        module.visit_with(&mut checker);
+    }
+}
+
+/// Client modules are given as import specifiers or as absolute paths, which
+/// relative imports are resolved to.
+fn is_client_module(filepath: &str, specifier: &str, client_modules: &[String]) -> bool {
+    if client_modules.iter().any(|module| module == specifier) {
+        return true;
+    }
+    if !specifier.starts_with('.') {
+        return false;
+    }
+
+    let resolved = match Path::new(filepath).parent() {
+        Some(dir) => normalize(&dir.join(specifier)),
+        None => return false,
+    };
+    client_modules.iter().map(Path::new).any(|module| {
+        // `./button` may resolve to `./button.js` or to `./button/index.js`.
+        let without_extension = module.with_extension("");
+        module == resolved
+            || without_extension == resolved
+            || without_extension == resolved.join("index")
+    })
+}
+
+/// Resolves `.` and `..` without touching the file system.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Classes whose instances can be passed to Client Components.
+const SERIALIZABLE_BUILT_INS: &[&str] = &[
+    "Date",
+    "Map",
+    "Set",
+    "RegExp",
+    "ArrayBuffer",
+    "DataView",
+    "Int8Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "Int16Array",
+    "Uint16Array",
+    "Int32Array",
+    "Uint32Array",
+    "Float32Array",
+    "Float64Array",
+    "BigInt64Array",
+    "BigUint64Array",
+];
+
+struct PropsChecker {
+    client_components: FxHashSet<Id>,
+    /// `import * as ui from './ui'`, rendered as `<ui.Button />`.
+    client_namespaces: FxHashSet<Id>,
+    /// Top level functions which are not Server Actions.
+    functions: FxHashSet<Id>,
+    classes: FxHashSet<Id>,
+}
+
+impl PropsChecker {
+    fn client_component_name(&self, name: &JSXElementName) -> Option<String> {
+        match name {
+            JSXElementName::Ident(ident) if self.client_components.contains(&ident.to_id()) => {
+                Some(ident.sym.to_string())
+            }
+            JSXElementName::JSXMemberExpr(JSXMemberExpr {
+                obj: JSXObject::Ident(obj),
+                prop,
+            }) if self.client_namespaces.contains(&obj.to_id()) => {
+                Some(format!("{}.{}", obj.sym, prop.sym))
+            }
+            _ => None,
+        }
+    }
+
+    /// What makes the value impossible to serialize, if anything.
+    fn check_value(&self, expr: &Expr) -> Option<(Span, &'static str)> {
+        match expr {
+            Expr::Paren(ParenExpr { expr, .. }) => self.check_value(expr),
+            Expr::Fn(FnExpr { function, .. }) if !is_action(function, false) => {
+                Some((function.span, "a function"))
+            }
+            Expr::Arrow(arrow) if !arrow_has_directive(arrow) => Some((arrow.span, "a function")),
+            Expr::Class(ClassExpr { class, .. }) => Some((class.span, "a class")),
+            Expr::New(NewExpr {
+                callee: box Expr::Ident(callee),
+                args,
+                ..
+            }) if SERIALIZABLE_BUILT_INS.contains(&&*callee.sym)
+                && !self.classes.contains(&callee.to_id()) =>
+            {
+                args.iter()
+                    .flatten()
+                    .find_map(|arg| self.check_value(&arg.expr))
+            }
+            Expr::New(NewExpr { span, .. }) => Some((*span, "a class instance")),
+            Expr::Ident(ident) if self.functions.contains(&ident.to_id()) => {
+                Some((ident.span, "a function"))
+            }
+            Expr::Ident(ident) if self.classes.contains(&ident.to_id()) => {
+                Some((ident.span, "a class"))
+            }
+            Expr::Object(ObjectLit { props, .. }) => props.iter().find_map(|prop| match prop {
+                PropOrSpread::Prop(box Prop::KeyValue(KeyValueProp { value, .. })) => {
+                    self.check_value(value)
+                }
+                PropOrSpread::Prop(box Prop::Method(MethodProp { function, .. })) => {
+                    Some((function.span, "a function"))
+                }
+                PropOrSpread::Prop(box Prop::Getter(GetterProp { span, .. }))
+                | PropOrSpread::Prop(box Prop::Setter(SetterProp { span, .. })) => {
+                    Some((*span, "an object with accessors"))
+                }
+                PropOrSpread::Prop(box Prop::Shorthand(ident)) => {
+                    self.check_value(&Expr::Ident(ident.clone()))
+                }
+                _ => None,
+            }),
+            Expr::Array(ArrayLit { elems, .. }) => elems
+                .iter()
+                .flatten()
+                .find_map(|elem| self.check_value(&elem.expr)),
+            _ => None,
+        }
+    }
+}
+
+impl Visit for PropsChecker {
+    noop_visit_type!();
+
+    fn visit_jsx_opening_element(&mut self, element: &JSXOpeningElement) {
+        if let Some(component) = self.client_component_name(&element.name) {
+            for attr in &element.attrs {
+                let (name, expr) = match attr {
+                    JSXAttrOrSpread::JSXAttr(JSXAttr {
+                        name,
+                        value:
+                            Some(JSXAttrValue::JSXExprContainer(JSXExprContainer {
+                                expr: JSXExpr::Expr(expr),
+                                ..
+                            })),
+                        ..
+                    }) => (name, expr),
+                    _ => continue,
+                };
+                let name = match name {
+                    JSXAttrName::Ident(ident) => ident.sym.to_string(),
+                    JSXAttrName::JSXNamespacedName(name) => {
+                        format!("{}:{}", name.ns.sym, name.name.sym)
+                    }
+                };
+
+                if let Some((span, kind)) = self.check_value(expr) {
+                    HANDLER.with(|handler| {
+                        handler
+                            .struct_span_err(
+                                span,
+                                &format!(
+                                    "The `{}` prop of `<{}>` is {}, which can't be passed from a \
+                                     Server Component to a Client Component. Only plain objects, \
+                                     arrays, primitives and Server Actions can be passed.",
+                                    name, component, kind
+                                ),
+                            )
+                            .emit()
+                    })
+                }
+            }
+        }
+
+        element.visit_children_with(self);
+    }
+}
+
+/// Functions with a "use server" directive, or the async functions of a
+/// "use server" file.
+fn is_action(function: &Function, is_action_entry: bool) -> bool {
+    function_has_directive(function) || (is_action_entry && function.is_async)
+}
@@ -0,0 +1,866 @@
+use fxhash::FxHashSet;
+use sha1::{Digest, Sha1};
+use std::mem::take;
+use swc_core::{
+    common::{errors::HANDLER, Span, Spanned, DUMMY_SP},
+    ecma::ast::*,
+    ecma::atoms::{js_word, JsWord},
+    ecma::utils::{private_ident, quote_ident, quote_str, ExprFactory},
+    ecma::visit::{noop_visit_mut_type, noop_visit_type, Visit, VisitMut, VisitMutWith, VisitWith},
+};
+
+use crate::{metadata::ServerAction, react_server_components::module_export_name};
+
+/// Given to anonymous default exports, so they can be registered.
+const DEFAULT_ACTION: &str = "$$ACTION_DEFAULT";
+/// Inline actions are moved to the top level of the module with this name,
+/// followed by their index in the module.
+const INLINE_ACTION: &str = "$$INLINE_ACTION_";
+
+/// An action found in a module, with the binding it is registered with.
+pub(crate) struct FoundAction {
+    pub name: JsWord,
+    pub local: Ident,
+}
+
+/// Only depends on the module and the name of the action, so it stays the same
+/// between builds and between the server and client layers. `filepath` should
+/// be relative to the root of the project, so it is the same on every machine
+/// too.
+pub fn action_id(filepath: &str, name: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(filepath.replace('\\', "/").as_bytes());
+    hasher.update(b":");
+    hasher.update(name.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Collects the exports of a module starting with "use server", which all
+/// have to be async functions.
+pub(crate) fn collect_file_actions(module: &mut Module) -> Vec<FoundAction> {
+    let async_locals = collect_async_locals(module);
+    let mut actions = vec![];
+    let mut body = Vec::with_capacity(module.body.len());
+
+    for mut item in take(&mut module.body) {
+        // `export default async () => {}` has no binding to register.
+        if let ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(export)) = &mut item {
+            if is_async_fn_expr(&export.expr) {
+                let local = quote_ident!(DEFAULT_ACTION);
+                body.push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                    span: DUMMY_SP,
+                    kind: VarDeclKind::Const,
+                    decls: vec![VarDeclarator {
+                        span: DUMMY_SP,
+                        name: Pat::Ident(local.clone().into()),
+                        init: Some(std::mem::replace(
+                            &mut export.expr,
+                            Box::new(Expr::Ident(local.clone())),
+                        )),
+                        definite: false,
+                    }],
+                    declare: false,
+                })))));
+                actions.push(FoundAction {
+                    name: js_word!("default"),
+                    local,
+                });
+                body.push(item);
+                continue;
+            }
+        }
+
+        if let ModuleItem::ModuleDecl(decl) = &mut item {
+            collect_exported_actions(decl, &async_locals, &mut actions);
+        }
+        body.push(item);
+    }
+
+    module.body = body;
+    actions
+}
+
+fn collect_exported_actions(
+    decl: &mut ModuleDecl,
+    async_locals: &FxHashSet<Id>,
+    actions: &mut Vec<FoundAction>,
+) {
+    match decl {
+        ModuleDecl::ExportDecl(ExportDecl { decl, .. }) => match decl {
+            Decl::Fn(f) if f.function.is_async => actions.push(FoundAction {
+                name: f.ident.sym.clone(),
+                local: f.ident.clone(),
+            }),
+            Decl::Var(var) => {
+                for decl in &var.decls {
+                    match (&decl.name, &decl.init) {
+                        (Pat::Ident(i), Some(init)) if is_async_fn_expr(init) => {
+                            actions.push(FoundAction {
+                                name: i.id.sym.clone(),
+                                local: i.id.clone(),
+                            })
+                        }
+                        _ => report_non_async_export(decl.span),
+                    }
+                }
+            }
+            Decl::TsInterface(..) | Decl::TsTypeAlias(..) => {}
+            decl => report_non_async_export(decl.span()),
+        },
+        ModuleDecl::ExportDefaultDecl(ExportDefaultDecl { decl, span }) => match decl {
+            DefaultDecl::Fn(f) if f.function.is_async => actions.push(FoundAction {
+                name: js_word!("default"),
+                local: f
+                    .ident
+                    .get_or_insert_with(|| quote_ident!(DEFAULT_ACTION))
+                    .clone(),
+            }),
+            DefaultDecl::TsInterfaceDecl(..) => {}
+            _ => report_non_async_export(*span),
+        },
+        ModuleDecl::ExportDefaultExpr(ExportDefaultExpr { expr, span }) => match &**expr {
+            Expr::Ident(i) if async_locals.contains(&i.to_id()) => actions.push(FoundAction {
+                name: js_word!("default"),
+                local: i.clone(),
+            }),
+            _ => report_non_async_export(*span),
+        },
+        // Re-exported actions are registered by the module declaring them.
+        ModuleDecl::ExportNamed(NamedExport {
+            src: None,
+            type_only: false,
+            specifiers,
+            ..
+        }) => {
+            for specifier in specifiers {
+                if let ExportSpecifier::Named(ExportNamedSpecifier {
+                    orig: ModuleExportName::Ident(orig),
+                    exported,
+                    is_type_only: false,
+                    span,
+                }) = specifier
+                {
+                    if !async_locals.contains(&orig.to_id()) {
+                        report_non_async_export(*span);
+                        continue;
+                    }
+                    actions.push(FoundAction {
+                        name: match exported {
+                            Some(ModuleExportName::Ident(i)) => i.sym.clone(),
+                            Some(ModuleExportName::Str(s)) => s.value.clone(),
+                            None => orig.sym.clone(),
+                        },
+                        local: orig.clone(),
+                    })
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collects the functions with a "use server" directive. Top level ones are
+/// named after their binding. Inline ones, declared in other functions or
+/// passed as arguments, are moved to the top level of the module and named
+/// after their index, which is the same on the server and client layers.
+pub(crate) fn collect_function_actions(module: &mut Module) -> Vec<FoundAction> {
+    let mut actions = vec![];
+    let mut top_level = vec![];
+
+    for item in &mut module.body {
+        let decl = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { decl, .. })) => decl,
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl: DefaultDecl::Fn(f),
+                ..
+            })) if function_has_directive(&f.function) => {
+                top_level.push(f.function.span);
+                if assert_async(f.function.span, f.function.is_async) {
+                    actions.push(FoundAction {
+                        name: js_word!("default"),
+                        local: f
+                            .ident
+                            .get_or_insert_with(|| quote_ident!(DEFAULT_ACTION))
+                            .clone(),
+                    });
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        match decl {
+            Decl::Fn(f) if function_has_directive(&f.function) => {
+                top_level.push(f.function.span);
+                if assert_async(f.function.span, f.function.is_async) {
+                    actions.push(FoundAction {
+                        name: f.ident.sym.clone(),
+                        local: f.ident.clone(),
+                    });
+                }
+            }
+            Decl::Var(var) => {
+                for decl in &var.decls {
+                    if let (Pat::Ident(i), Some(init)) = (&decl.name, &decl.init) {
+                        if let Some((span, is_async)) = fn_expr_with_directive(init) {
+                            top_level.push(span);
+                            if assert_async(span, is_async) {
+                                actions.push(FoundAction {
+                                    name: i.id.sym.clone(),
+                                    local: i.id.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut inline = InlineActions {
+        top_level: &top_level,
+        scopes: vec![],
+        checked: vec![],
+        hoisted: vec![],
+        actions: vec![],
+    };
+    module.visit_mut_with(&mut inline);
+    let InlineActions {
+        checked,
+        hoisted,
+        actions: inline_actions,
+        ..
+    } = inline;
+
+    // Methods have a "use server" directive but no binding to replace.
+    for span in find_directives(module) {
+        if !top_level.contains(&span) && !checked.contains(&span) {
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_err(
+                        span,
+                        "Methods can't be Server Actions. Declare the Server Action as a function \
+                         instead.",
+                    )
+                    .emit()
+            })
+        }
+    }
+
+    // After the imports, so the actions are declared before the code of the
+    // module runs.
+    let index = module
+        .body
+        .iter()
+        .position(|item| !matches!(item, ModuleItem::ModuleDecl(ModuleDecl::Import(..))))
+        .unwrap_or(module.body.len());
+    module.body.splice(index..index, hoisted);
+
+    actions.extend(inline_actions);
+    actions
+}
+
+/// Moves the functions with a "use server" directive which aren't at the top
+/// level of the module there, and replaces them with their new binding.
+struct InlineActions<'a> {
+    top_level: &'a [Span],
+    /// Names declared by each of the functions the visitor is in.
+    scopes: Vec<FxHashSet<JsWord>>,
+    /// Spans of the inline actions, including the ones which can't be
+    /// hoisted.
+    checked: Vec<Span>,
+    /// The declarations of the hoisted actions.
+    hoisted: Vec<ModuleItem>,
+    actions: Vec<FoundAction>,
+}
+
+impl InlineActions<'_> {
+    /// Returns the binding of the action, unless it can't be hoisted.
+    fn hoist<N>(&mut self, span: Span, is_async: bool, node: &N) -> Option<Ident>
+    where
+        N: VisitWith<DeclaredNames> + VisitWith<ReferencedNames>,
+    {
+        if self.top_level.contains(&span) {
+            return None;
+        }
+        self.checked.push(span);
+        if !assert_async(span, is_async) {
+            return None;
+        }
+
+        let mut declared = DeclaredNames {
+            nested: true,
+            ..Default::default()
+        };
+        node.visit_with(&mut declared);
+        let mut referenced = ReferencedNames::default();
+        node.visit_with(&mut referenced);
+
+        let mut captured: Vec<&str> = referenced
+            .names
+            .iter()
+            .filter(|name| {
+                !declared.names.contains(*name)
+                    && self.scopes.iter().any(|scope| scope.contains(*name))
+            })
+            .map(|name| &**name)
+            .collect();
+        if !captured.is_empty() {
+            captured.sort();
+            HANDLER.with(|handler| {
+                handler
+                    .struct_span_err(
+                        span,
+                        &format!(
+                            "Inline Server Actions can't use the variables of the functions they \
+                             are declared in, like {}. Pass the values as arguments instead.",
+                            captured
+                                .iter()
+                                .map(|name| format!("`{}`", name))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )
+                    .emit()
+            });
+            return None;
+        }
+
+        let local = quote_ident!(format!("{}{}", INLINE_ACTION, self.actions.len()));
+        self.actions.push(FoundAction {
+            name: local.sym.clone(),
+            local: local.clone(),
+        });
+        Some(local)
+    }
+}
+
+impl VisitMut for InlineActions<'_> {
+    noop_visit_mut_type!();
+
+    fn visit_mut_function(&mut self, function: &mut Function) {
+        let mut declared = DeclaredNames::default();
+        function.params.visit_with(&mut declared);
+        function.body.visit_with(&mut declared);
+
+        self.scopes.push(declared.names);
+        function.visit_mut_children_with(self);
+        self.scopes.pop();
+    }
+
+    fn visit_mut_arrow_expr(&mut self, arrow: &mut ArrowExpr) {
+        let mut declared = DeclaredNames::default();
+        arrow.params.visit_with(&mut declared);
+        arrow.body.visit_with(&mut declared);
+
+        self.scopes.push(declared.names);
+        arrow.visit_mut_children_with(self);
+        self.scopes.pop();
+    }
+
+    // `async function save() { "use server" }` becomes
+    // `const save = $$INLINE_ACTION_0`, at the start of the block so it can
+    // be used before, like the function.
+    fn visit_mut_stmts(&mut self, stmts: &mut Vec<Stmt>) {
+        let mut bindings = vec![];
+        for mut stmt in take(stmts) {
+            stmt.visit_mut_with(self);
+
+            match stmt {
+                Stmt::Decl(Decl::Fn(mut f)) if function_has_directive(&f.function) => {
+                    match self.hoist(f.function.span, f.function.is_async, &*f.function) {
+                        Some(local) => {
+                            let name = std::mem::replace(&mut f.ident, local.clone());
+                            bindings
+                                .push(Stmt::Decl(const_decl(name, Box::new(Expr::Ident(local)))));
+                            self.hoisted.push(ModuleItem::Stmt(Stmt::Decl(Decl::Fn(f))));
+                        }
+                        None => stmts.push(Stmt::Decl(Decl::Fn(f))),
+                    }
+                }
+                stmt => stmts.push(stmt),
+            }
+        }
+        stmts.splice(0..0, bindings);
+    }
+
+    fn visit_mut_expr(&mut self, expr: &mut Expr) {
+        expr.visit_mut_children_with(self);
+
+        let local = match expr {
+            Expr::Fn(f) if function_has_directive(&f.function) => {
+                self.hoist(f.function.span, f.function.is_async, &*f.function)
+            }
+            Expr::Arrow(arrow) if arrow_has_directive(arrow) => {
+                self.hoist(arrow.span, arrow.is_async, &*arrow)
+            }
+            _ => None,
+        };
+        if let Some(local) = local {
+            let init = std::mem::replace(expr, Expr::Ident(local.clone()));
+            self.hoisted.push(ModuleItem::Stmt(Stmt::Decl(const_decl(
+                local,
+                Box::new(init),
+            ))));
+        }
+    }
+}
+
+/// Names declared by a function, including the ones of the functions nested
+/// in it when `nested` is set.
+#[derive(Default)]
+struct DeclaredNames {
+    names: FxHashSet<JsWord>,
+    nested: bool,
+}
+
+impl Visit for DeclaredNames {
+    noop_visit_type!();
+
+    fn visit_binding_ident(&mut self, ident: &BindingIdent) {
+        self.names.insert(ident.id.sym.clone());
+    }
+
+    // `{ id }` in `function Page({ id }) {}`
+    fn visit_assign_pat_prop(&mut self, prop: &AssignPatProp) {
+        self.names.insert(prop.key.sym.clone());
+        prop.visit_children_with(self);
+    }
+
+    fn visit_fn_decl(&mut self, f: &FnDecl) {
+        self.names.insert(f.ident.sym.clone());
+        f.visit_children_with(self);
+    }
+
+    fn visit_class_decl(&mut self, c: &ClassDecl) {
+        self.names.insert(c.ident.sym.clone());
+        c.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, function: &Function) {
+        if self.nested {
+            function.visit_children_with(self);
+        }
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+        if self.nested {
+            arrow.visit_children_with(self);
+        }
+    }
+
+    fn visit_class(&mut self, class: &Class) {
+        if self.nested {
+            class.visit_children_with(self);
+        }
+    }
+}
+
+/// Names used as values, like `id` in `db.save(id, { id })`, but not `save`.
+#[derive(Default)]
+struct ReferencedNames {
+    names: FxHashSet<JsWord>,
+}
+
+impl Visit for ReferencedNames {
+    noop_visit_type!();
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let Expr::Ident(ident) = expr {
+            self.names.insert(ident.sym.clone());
+        }
+        expr.visit_children_with(self);
+    }
+
+    fn visit_prop(&mut self, prop: &Prop) {
+        if let Prop::Shorthand(ident) = prop {
+            self.names.insert(ident.sym.clone());
+        }
+        prop.visit_children_with(self);
+    }
+
+    fn visit_jsx_element_name(&mut self, name: &JSXElementName) {
+        match name {
+            JSXElementName::Ident(ident) if !ident.sym.starts_with(char::is_lowercase) => {
+                self.names.insert(ident.sym.clone());
+            }
+            JSXElementName::JSXMemberExpr(JSXMemberExpr {
+                obj: JSXObject::Ident(ident),
+                ..
+            }) => {
+                self.names.insert(ident.sym.clone());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Client Components can only import Server Actions from modules starting with
+/// "use server".
+pub(crate) fn assert_no_function_actions(module: &Module) {
+    for span in find_directives(module) {
+        HANDLER.with(|handler| {
+            handler
+                .struct_span_err(
+                    span,
+                    "Server Actions can't be declared in Client Components. Move them to a \
+                     separate file with \"use server\" at the top, and import them from there.",
+                )
+                .emit()
+        })
+    }
+}
+
+/// Registers the actions with the server runtime, which looks them up by ID.
+pub(crate) fn register_actions(module: &mut Module, actions: &[(ServerAction, Ident)]) {
+    let register = private_ident!("$$registerServerAction");
+
+    module.body.insert(
+        0,
+        ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            span: DUMMY_SP,
+            specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+                span: DUMMY_SP,
+                local: register.clone(),
+                imported: Some(ModuleExportName::Ident(quote_ident!(
+                    "registerServerAction"
+                ))),
+                is_type_only: false,
+            })],
+            src: Box::new(quote_str!("private-next-rsc-action-registry")),
+            type_only: false,
+            asserts: None,
+        })),
+    );
+
+    for (action, local) in actions {
+        module.body.push(ModuleItem::Stmt(Stmt::Expr(ExprStmt {
+            span: DUMMY_SP,
+            expr: Box::new(Expr::Call(CallExpr {
+                span: DUMMY_SP,
+                callee: register.clone().as_callee(),
+                args: vec![
+                    quote_str!(&*action.id).as_arg(),
+                    Expr::Ident(local.clone()).as_arg(),
+                ],
+                type_args: Default::default(),
+            })),
+        })));
+    }
+}
+
+/// Replaces the module with stubs calling the actions on the server by ID.
+pub(crate) fn to_action_proxies(module: &mut Module, actions: &[ServerAction]) {
+    let proxy_ident = quote_ident!("createActionProxy");
+
+    module.body.clear();
+    module
+        .body
+        .push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+            span: DUMMY_SP,
+            specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+                span: DUMMY_SP,
+                local: proxy_ident.clone(),
+                imported: None,
+                is_type_only: false,
+            })],
+            src: Box::new(quote_str!("private-next-rsc-action-proxy")),
+            type_only: false,
+            asserts: None,
+        })));
+
+    let mut specifiers = vec![];
+    for (i, action) in actions.iter().enumerate() {
+        let local = quote_ident!(format!("$$ACTION_{}", i));
+        module
+            .body
+            .push(ModuleItem::Stmt(Stmt::Decl(Decl::Var(Box::new(VarDecl {
+                span: DUMMY_SP,
+                kind: VarDeclKind::Const,
+                decls: vec![VarDeclarator {
+                    span: DUMMY_SP,
+                    name: Pat::Ident(local.clone().into()),
+                    init: Some(Box::new(Expr::Call(CallExpr {
+                        span: DUMMY_SP,
+                        callee: proxy_ident.clone().as_callee(),
+                        args: vec![quote_str!(&*action.id).as_arg()],
+                        type_args: Default::default(),
+                    }))),
+                    definite: false,
+                }],
+                declare: false,
+            })))));
+
+        specifiers.push(ExportSpecifier::Named(ExportNamedSpecifier {
+            span: DUMMY_SP,
+            orig: ModuleExportName::Ident(local),
+            exported: Some(module_export_name(&action.name)),
+            is_type_only: false,
+        }));
+    }
+
+    if !specifiers.is_empty() {
+        module
+            .body
+            .push(ModuleItem::ModuleDecl(ModuleDecl::ExportNamed(
+                NamedExport {
+                    span: DUMMY_SP,
+                    specifiers,
+                    src: None,
+                    type_only: false,
+                    asserts: None,
+                },
+            )));
+    }
+}
+
+/// `const name = init`
+fn const_decl(name: Ident, init: Box<Expr>) -> Decl {
+    Decl::Var(Box::new(VarDecl {
+        span: DUMMY_SP,
+        kind: VarDeclKind::Const,
+        decls: vec![VarDeclarator {
+            span: DUMMY_SP,
+            name: Pat::Ident(name.into()),
+            init: Some(init),
+            definite: false,
+        }],
+        declare: false,
+    }))
+}
+
+/// Replaces the functions with a "use server" directive of a module which
+/// isn't a Server Action file with stubs calling them on the server by ID.
+/// The rest of the module is kept.
+pub(crate) fn to_function_action_proxies(module: &mut Module, actions: &[(ServerAction, Ident)]) {
+    if actions.is_empty() {
+        return;
+    }
+
+    let proxy_ident = private_ident!("$$createActionProxy");
+    let stub = |local: &Ident| {
+        actions
+            .iter()
+            .find(|(_, action_local)| action_local.to_id() == local.to_id())
+            .map(|(action, _)| {
+                Box::new(Expr::Call(CallExpr {
+                    span: DUMMY_SP,
+                    callee: proxy_ident.clone().as_callee(),
+                    args: vec![quote_str!(&*action.id).as_arg()],
+                    type_args: Default::default(),
+                }))
+            })
+    };
+    let stub_decl = |decl: Decl| match decl {
+        Decl::Fn(f) => match stub(&f.ident) {
+            Some(init) => const_decl(f.ident, init),
+            None => Decl::Fn(f),
+        },
+        Decl::Var(mut var) => {
+            for decl in &mut var.decls {
+                if let Pat::Ident(BindingIdent { id, .. }) = &decl.name {
+                    if let Some(init) = stub(id) {
+                        decl.init = Some(init);
+                    }
+                }
+            }
+            Decl::Var(var)
+        }
+        decl => decl,
+    };
+
+    let mut body = Vec::with_capacity(module.body.len() + 1);
+    body.push(ModuleItem::ModuleDecl(ModuleDecl::Import(ImportDecl {
+        span: DUMMY_SP,
+        specifiers: vec![ImportSpecifier::Named(ImportNamedSpecifier {
+            span: DUMMY_SP,
+            local: proxy_ident.clone(),
+            imported: Some(ModuleExportName::Ident(quote_ident!("createActionProxy"))),
+            is_type_only: false,
+        })],
+        src: Box::new(quote_str!("private-next-rsc-action-proxy")),
+        type_only: false,
+        asserts: None,
+    })));
+
+    for item in take(&mut module.body) {
+        match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => {
+                body.push(ModuleItem::Stmt(Stmt::Decl(stub_decl(decl))))
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl { span, decl })) => {
+                body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                    span,
+                    decl: stub_decl(decl),
+                })))
+            }
+            // `export default async function () { "use server" }`
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(ExportDefaultDecl {
+                decl:
+                    DefaultDecl::Fn(FnExpr {
+                        ident: Some(ident),
+                        function,
+                    }),
+                span,
+            })) => match stub(&ident) {
+                Some(init) => {
+                    body.push(ModuleItem::Stmt(Stmt::Decl(const_decl(
+                        ident.clone(),
+                        init,
+                    ))));
+                    body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultExpr(
+                        ExportDefaultExpr {
+                            span,
+                            expr: Box::new(Expr::Ident(ident)),
+                        },
+                    )));
+                }
+                None => body.push(ModuleItem::ModuleDecl(ModuleDecl::ExportDefaultDecl(
+                    ExportDefaultDecl {
+                        decl: DefaultDecl::Fn(FnExpr {
+                            ident: Some(ident),
+                            function,
+                        }),
+                        span,
+                    },
+                ))),
+            },
+            item => body.push(item),
+        }
+    }
+
+    module.body = body;
+}
+
+fn collect_async_locals(module: &Module) -> FxHashSet<Id> {
+    let mut locals = FxHashSet::default();
+    for item in &module.body {
+        match item {
+            ModuleItem::Stmt(Stmt::Decl(Decl::Fn(f)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Fn(f),
+                ..
+            })) if f.function.is_async => {
+                locals.insert(f.ident.to_id());
+            }
+            ModuleItem::Stmt(Stmt::Decl(Decl::Var(var)))
+            | ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(ExportDecl {
+                decl: Decl::Var(var),
+                ..
+            })) => {
+                for decl in &var.decls {
+                    if let (Pat::Ident(i), Some(init)) = (&decl.name, &decl.init) {
+                        if is_async_fn_expr(init) {
+                            locals.insert(i.to_id());
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    locals
+}
+
+fn is_async_fn_expr(expr: &Expr) -> bool {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. }) => is_async_fn_expr(expr),
+        Expr::Fn(f) => f.function.is_async,
+        Expr::Arrow(arrow) => arrow.is_async,
+        _ => false,
+    }
+}
+
+/// The span of a function expression with a "use server" directive, and
+/// whether it is async.
+fn fn_expr_with_directive(expr: &Expr) -> Option<(Span, bool)> {
+    match expr {
+        Expr::Paren(ParenExpr { expr, .. }) => fn_expr_with_directive(expr),
+        Expr::Fn(f) if function_has_directive(&f.function) => {
+            Some((f.function.span, f.function.is_async))
+        }
+        Expr::Arrow(arrow) if arrow_has_directive(arrow) => Some((arrow.span, arrow.is_async)),
+        _ => None,
+    }
+}
+
+pub(crate) fn function_has_directive(function: &Function) -> bool {
+    match &function.body {
+        Some(body) => has_use_server_directive(&body.stmts),
+        None => false,
+    }
+}
+
+pub(crate) fn arrow_has_directive(arrow: &ArrowExpr) -> bool {
+    match &arrow.body {
+        BlockStmtOrExpr::BlockStmt(body) => has_use_server_directive(&body.stmts),
+        BlockStmtOrExpr::Expr(..) => false,
+    }
+}
+
+fn has_use_server_directive(stmts: &[Stmt]) -> bool {
+    stmts
+        .iter()
+        .map_while(|stmt| match stmt {
+            Stmt::Expr(ExprStmt {
+                expr: box Expr::Lit(Lit::Str(Str { value, .. })),
+                ..
+            }) => Some(value),
+            _ => None,
+        })
+        .any(|value| &**value == "use server")
+}
+
+fn assert_async(span: Span, is_async: bool) -> bool {
+    if !is_async {
+        HANDLER.with(|handler| {
+            handler
+                .struct_span_err(span, "Server Actions have to be async functions.")
+                .emit()
+        })
+    }
+    is_async
+}
+
+fn report_non_async_export(span: Span) {
+    HANDLER.with(|handler| {
+        handler
+            .struct_span_err(
+                span,
+                "Only async functions can be exported from a \"use server\" file.",
+            )
+            .emit()
+    })
+}
+
+/// Spans of all functions with a "use server" directive.
+fn find_directives(module: &Module) -> Vec<Span> {
+    let mut finder = DirectiveFinder::default();
+    module.visit_with(&mut finder);
+    finder.spans
+}
+
+#[derive(Default)]
+struct DirectiveFinder {
+    spans: Vec<Span>,
+}
+
+impl Visit for DirectiveFinder {
+    noop_visit_type!();
+
+    fn visit_function(&mut self, function: &Function) {
+        if function_has_directive(function) {
+            self.spans.push(function.span);
+        }
+        function.visit_children_with(self);
+    }
+
+    fn visit_arrow_expr(&mut self, arrow: &ArrowExpr) {
+        if arrow_has_directive(arrow) {
+            self.spans.push(arrow.span);
+        }
+        arrow.visit_children_with(self);
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    path::Path,
+};
+
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use swc_core::{
+    common::{
+        errors::{Handler, HANDLER},
+        sync::Lrc,
+        FilePathMapping, SourceMap, GLOBALS,
+    },
+    ecma::ast::*,
+    ecma::parser::{parse_file_as_module, EsConfig, Syntax, TsConfig},
+    ecma::visit::{noop_visit_type, Visit, VisitWith},
+};
+
+use crate::react_server_components::{
+    collect_top_level_directives_and_imports, denied_message, find_denied, Denied, DenyList,
+    ModuleImports, INVALID_CLIENT_IMPORTS, INVALID_SERVER_IMPORTS,
+};
+
+/// Only the deny-lists of modules are checked. The APIs denied by
+/// `invalidServerApis` are imported by name, so they are reported when the
+/// file using them is transformed, and don't depend on the rest of the graph.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphOptions {
+    /// Files rendered as Server Components, like the pages and layouts of
+    /// `app`. Entries starting with "use client" are Client Components.
+    pub entries: Vec<String>,
+    /// Resolved paths of the imports of each file, by file and then by
+    /// specifier. Imports missing here, like packages, are not followed.
+    pub resolved: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    pub invalid_server_imports: DenyList,
+    #[serde(default)]
+    pub invalid_client_imports: DenyList,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundaryViolation {
+    /// The error `react_server_components` reports for the import, like
+    /// `NEXT_RSC_ERR_SERVER_IMPORT: client-only`.
+    pub message: String,
+    /// The imported specifier.
+    pub source: String,
+    /// Files from the entry to the one importing `source`.
+    pub chain: Vec<String>,
+}
+
+impl fmt::Display for BoundaryViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.message)?;
+        writeln!(f)?;
+        write!(f, "Import trace:")?;
+        for file in &self.chain {
+            write!(f, "\n  {}", file)?;
+        }
+        Ok(())
+    }
+}
+
+/// Follows the imports of the entries, and reports the modules which can't be
+/// imported by Server Components or by Client Components, like the
+/// `react_server_components` pass does for a single file. Every forbidden
+/// import is reported once, with the shortest chain leading to it.
+pub fn validate_server_components_graph(options: &GraphOptions) -> Vec<BoundaryViolation> {
+    let invalid_server_imports = options.invalid_server_imports.apply(INVALID_SERVER_IMPORTS);
+    let invalid_client_imports = options.invalid_client_imports.apply(INVALID_CLIENT_IMPORTS);
+
+    let cm: Lrc<SourceMap> = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+    // Misplaced directives are reported when the files are transformed.
+    let handler = Handler::with_emitter_writer(Box::new(std::io::sink()), None);
+
+    GLOBALS.set(&Default::default(), || {
+        HANDLER.set(&handler, || {
+            let mut graph = Graph {
+                cm,
+                modules: Default::default(),
+                parents: Default::default(),
+            };
+            let mut violations = vec![];
+            let mut queue: VecDeque<(&str, bool)> = VecDeque::new();
+
+            for entry in options.entries.iter().map(String::as_str) {
+                if graph.parents.insert((entry, true), None).is_none() {
+                    queue.push_back((entry, true));
+                }
+            }
+
+            while let Some((file, is_server)) = queue.pop_front() {
+                let (is_client_entry, sources) = match graph.module(file) {
+                    Some(module) => (module.is_client_entry, module.sources.clone()),
+                    None => continue,
+                };
+                // Everything imported by a Client Component is on the client.
+                if is_server && is_client_entry {
+                    if graph.parents.contains_key(&(file, false)) {
+                        continue;
+                    }
+                    let parent = graph.parents[&(file, true)];
+                    graph.parents.insert((file, false), parent);
+                }
+                let is_server = is_server && !is_client_entry;
+                let (denied, code) = if is_server {
+                    (&invalid_server_imports, "NEXT_RSC_ERR_SERVER_IMPORT")
+                } else {
+                    (&invalid_client_imports, "NEXT_RSC_ERR_CLIENT_IMPORT")
+                };
+
+                let mut reported: FxHashSet<&str> = Default::default();
+                for source in &sources {
+                    if let Some(denied) = find_denied(denied, &source.as_str().into()) {
+                        if reported.insert(source) {
+                            violations.push(graph.violation(file, is_server, source, code, denied));
+                        }
+                    }
+
+                    if let Some(resolved) = options
+                        .resolved
+                        .get(file)
+                        .and_then(|resolved| resolved.get(source))
+                    {
+                        let next = (resolved.as_str(), is_server);
+                        if !graph.parents.contains_key(&next) {
+                            graph.parents.insert(next, Some((file, is_server)));
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+
+            violations
+        })
+    })
+}
+
+struct ModuleInfo {
+    is_client_entry: bool,
+    sources: Vec<String>,
+}
+
+struct Graph<'a> {
+    cm: Lrc<SourceMap>,
+    /// `None` for files which could not be read or parsed.
+    modules: FxHashMap<&'a str, Option<ModuleInfo>>,
+    /// The importer each file was first reached from, on the server or the
+    /// client, for the import chains.
+    parents: FxHashMap<(&'a str, bool), Option<(&'a str, bool)>>,
+}
+
+impl<'a> Graph<'a> {
+    fn module(&mut self, file: &'a str) -> Option<&ModuleInfo> {
+        let cm = &self.cm;
+        self.modules
+            .entry(file)
+            .or_insert_with(|| parse_module(cm, file))
+            .as_ref()
+    }
+
+    fn violation(
+        &self,
+        file: &'a str,
+        is_server: bool,
+        source: &str,
+        code: &str,
+        denied: &Denied,
+    ) -> BoundaryViolation {
+        let mut chain = vec![file.to_string()];
+        let mut current = (file, is_server);
+        while let Some(Some(parent)) = self.parents.get(&current) {
+            chain.push(parent.0.to_string());
+            current = *parent;
+        }
+        chain.reverse();
+
+        BoundaryViolation {
+            message: denied_message(code, denied, source),
+            source: source.to_string(),
+            chain,
+        }
+    }
+}
+
+fn parse_module(cm: &Lrc<SourceMap>, file: &str) -> Option<ModuleInfo> {
+    let path = Path::new(file);
+    let syntax = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("ts" | "mts" | "cts") => Syntax::Typescript(Default::default()),
+        Some("tsx") => Syntax::Typescript(TsConfig {
+            tsx: true,
+            ..Default::default()
+        }),
+        _ => Syntax::Es(EsConfig {
+            jsx: true,
+            ..Default::default()
+        }),
+    };
+
+    let fm = cm.load_file(path).ok()?;
+    let mut module =
+        parse_file_as_module(&fm, syntax, EsVersion::latest(), None, &mut vec![]).ok()?;
+    let (is_client_entry, _, imports) = collect_top_level_directives_and_imports(&mut module);
+
+    let mut sources = ModuleSources {
+        sources: imports
+            .into_iter()
+            .map(|ModuleImports { source, .. }| source.0.to_string())
+            .collect(),
+    };
+    module.visit_with(&mut sources);
+
+    Some(ModuleInfo {
+        is_client_entry,
+        sources: sources.sources,
+    })
+}
+
+/// Collects the modules re-exported with `export ... from`, and the ones
+/// loaded with `require()` or `import()`, after the imports.
+struct ModuleSources {
+    sources: Vec<String>,
+}
+
+impl ModuleSources {
+    fn add(&mut self, source: &Str) {
+        let source = source.value.to_string();
+        if !self.sources.contains(&source) {
+            self.sources.push(source);
+        }
+    }
+}
+
+impl Visit for ModuleSources {
+    noop_visit_type!();
+
+    fn visit_export_all(&mut self, export: &ExportAll) {
+        self.add(&export.src);
+    }
+
+    fn visit_named_export(&mut self, export: &NamedExport) {
+        if let Some(src) = &export.src {
+            self.add(src);
+        }
+    }
+
+    fn visit_call_expr(&mut self, call: &CallExpr) {
+        let is_load = match &call.callee {
+            Callee::Import(_) => true,
+            Callee::Expr(callee) => {
+                matches!(&**callee, Expr::Ident(Ident { sym, .. }) if &**sym == "require")
+            }
+            Callee::Super(_) => false,
+        };
+        if is_load {
+            if let Some(ExprOrSpread { spread: None, expr }) = call.args.first() {
+                if let Expr::Lit(Lit::Str(source)) = &**expr {
+                    self.add(source);
+                }
+            }
+        }
+        call.visit_children_with(self);
+    }
+}
@@ -1,3 +1,4 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use swc_core::{
@@ -8,8 +9,9 @@ use swc_core::{
     ecma::visit::{Fold, FoldWith},
 };
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, JsonSchema)]
 pub struct Config {
+    #[schemars(with = "Vec<String>")]
     pub ignore: Vec<JsWord>,
 }
 
@@ -0,0 +1,109 @@
+use std::{
+    fs,
+    io::Result,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use lru::LruCache;
+use serde::{de::DeserializeOwned, Serialize};
+use sha1::{Digest, Sha1};
+
+/// Content-addressed cache of transform results, with an in-memory LRU in
+/// front of an optional cache directory.
+pub struct TransformCache<T> {
+    memory: Option<Mutex<LruCache<String, T>>>,
+    directory: Option<PathBuf>,
+}
+
+impl<T: Clone + Serialize + DeserializeOwned> TransformCache<T> {
+    /// `max_entries` bounds the in-memory cache, and `directory` additionally
+    /// persists entries across processes. Returns `None` when neither is
+    /// enabled.
+    pub fn new(max_entries: usize, directory: Option<PathBuf>) -> Result<Option<Self>> {
+        if let Some(directory) = &directory {
+            fs::create_dir_all(directory)?;
+        }
+        if max_entries == 0 && directory.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(TransformCache {
+            memory: if max_entries == 0 {
+                None
+            } else {
+                Some(Mutex::new(LruCache::new(max_entries)))
+            },
+            directory,
+        }))
+    }
+
+    pub fn get(&self, key: &str) -> Option<T> {
+        if let Some(memory) = &self.memory {
+            if let Some(cached) = memory.lock().unwrap().get(&key.to_owned()) {
+                return Some(cached.clone());
+            }
+        }
+
+        let directory = self.directory.as_ref()?;
+        let cached: T = fs::read(directory.join(format!("{}.json", key)))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())?;
+        if let Some(memory) = &self.memory {
+            memory.lock().unwrap().put(key.to_owned(), cached.clone());
+        }
+        Some(cached)
+    }
+
+    pub fn put(&self, key: String, value: &T) {
+        // Failing to persist an entry only costs a future cache miss.
+        if let Some(directory) = &self.directory {
+            if let Ok(content) = serde_json::to_vec(value) {
+                let path = directory.join(format!("{}.json", key));
+                let tmp_path = directory.join(format!("{}.json.{}", key, std::process::id()));
+                if fs::write(&tmp_path, content).is_ok() {
+                    let _ = fs::rename(&tmp_path, &path);
+                }
+            }
+        }
+        if let Some(memory) = &self.memory {
+            memory.lock().unwrap().put(key, value.clone());
+        }
+    }
+}
+
+/// The cache key of a transform. It covers the given parts (like the version,
+/// the source and the serialized options) and the files the passes read,
+/// like wasm plugins. Files are fingerprinted by their size and modification
+/// time, so a plugin rebuilt at the same path is a cache miss.
+pub fn cache_key(parts: &[&[u8]], files: &[&Path]) -> String {
+    let mut hasher = Sha1::new();
+    let mut update = |part: &[u8]| {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    };
+
+    for part in parts {
+        update(part);
+    }
+    for file in files {
+        update(file.to_string_lossy().as_bytes());
+        // Missing files hash like empty ones, and fail the transform anyway.
+        let (len, modified) = fs::metadata(file)
+            .map(|metadata| {
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|modified| modified.as_nanos())
+                    .unwrap_or_default();
+                (metadata.len(), modified)
+            })
+            .unwrap_or_default();
+        update(&len.to_le_bytes());
+        update(&modified.to_le_bytes());
+    }
+
+    format!("{:x}", hasher.finalize())
+}
@@ -0,0 +1,112 @@
+//! Runs the passes through the swc `Compiler`, like the bindings do. Every
+//! test crate uses a part of these.
+#![allow(dead_code)]
+
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+use next_swc::{
+    custom_before_pass,
+    diagnostics::{with_diagnostics, Diagnostic},
+    metadata::ModuleMetadata,
+    react_server_components::{server_components, Config, Options},
+    PassContext, TransformOptions,
+};
+use once_cell::sync::Lazy;
+
+use swc_core::{
+    base::{config::Options as SwcOptions, try_with_handler, Compiler},
+    common::{
+        comments::SingleThreadedComments, FileName, FilePathMapping, Mark, SourceMap, GLOBALS,
+    },
+    ecma::transforms::base::pass::noop,
+};
+
+pub static COMPILER: Lazy<Arc<Compiler>> = Lazy::new(|| {
+    let cm = Arc::new(SourceMap::new(FilePathMapping::empty()));
+
+    Arc::new(Compiler::new(cm))
+});
+
+/// Runs the `react_server_components` pass on `src`, and returns whether the
+/// file was transformed without errors and the diagnostics reported.
+pub fn transform_server_components(
+    filename: &str,
+    src: &str,
+    swc_options: &SwcOptions,
+    options: Options,
+    metadata: Option<Rc<RefCell<ModuleMetadata>>>,
+) -> (bool, Vec<Diagnostic>) {
+    let fm = COMPILER
+        .cm
+        .new_source_file(FileName::Real(filename.into()), src.to_owned());
+
+    let mut diagnostics = vec![];
+    let result = try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            let (res, collected) = with_diagnostics(COMPILER.cm.clone(), handler, |handler| {
+                let comments = SingleThreadedComments::default();
+                COMPILER.process_js_with_custom_pass(
+                    fm.clone(),
+                    None,
+                    handler,
+                    swc_options,
+                    comments.clone(),
+                    |_| {
+                        server_components(
+                            fm.name.clone(),
+                            Config::WithOptions(options.clone()),
+                            comments.clone(),
+                            metadata.clone(),
+                        )
+                    },
+                    |_| noop(),
+                )
+            });
+            diagnostics = collected;
+            res
+        })
+    });
+
+    (result.is_ok(), diagnostics)
+}
+
+/// Runs `custom_before_pass` on `src` with the `options` given to the
+/// bindings, and returns what the passes collected.
+pub fn transform_with_custom_pass(src: &str, options: serde_json::Value) -> PassContext {
+    let options: TransformOptions = serde_json::from_value(options).unwrap();
+    let fm = COMPILER.cm.new_source_file(
+        FileName::Real(options.swc.filename.clone().into()),
+        src.to_owned(),
+    );
+
+    let context = PassContext::default();
+    try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            let mut options = options.patch(&fm);
+            let unresolved_mark = Mark::new();
+            options.swc.unresolved_mark = Some(unresolved_mark);
+            let comments = SingleThreadedComments::default();
+            COMPILER.process_js_with_custom_pass(
+                fm.clone(),
+                None,
+                handler,
+                &options.swc,
+                comments.clone(),
+                |_| {
+                    custom_before_pass(
+                        COMPILER.cm.clone(),
+                        fm.clone(),
+                        &options,
+                        comments.clone(),
+                        context.clone(),
+                        unresolved_mark,
+                    )
+                },
+                |_| noop(),
+            )
+        })
+    })
+    .unwrap();
+
+    context
+}
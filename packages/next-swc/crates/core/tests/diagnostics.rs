@@ -0,0 +1,341 @@
+use next_swc::{
+    diagnostics::{Diagnostic, Severity},
+    react_server_components::{DenyList, DenyListEntry, Options},
+};
+
+use crate::common::transform_server_components;
+
+mod common;
+
+fn diagnose(src: &str, options: Options) -> Vec<Diagnostic> {
+    let swc_options: swc_core::base::config::Options = serde_json::from_value(
+        serde_json::json!({ "jsc": { "parser": { "syntax": "ecmascript", "jsx": true } } }),
+    )
+    .unwrap();
+
+    let (ok, diagnostics) = transform_server_components(
+        "/some-project/src/some-file.js",
+        src,
+        &swc_options,
+        options,
+        None,
+    );
+
+    assert!(!ok);
+    diagnostics
+}
+
+#[test]
+fn should_collect_structured_diagnostics() {
+    let diagnostics = diagnose(
+        r#"import { useState } from 'react'
+import 'client-only'
+"#,
+        Options {
+            is_server: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "NEXT_RSC_ERR_REACT_API: useState");
+    assert_eq!(
+        diagnostics[0].code.as_deref(),
+        Some("NEXT_RSC_ERR_REACT_API")
+    );
+    assert_eq!(
+        diagnostics[0].filename.as_deref(),
+        Some("/some-project/src/some-file.js")
+    );
+    let start = diagnostics[0].start.unwrap();
+    let end = diagnostics[0].end.unwrap();
+    assert_eq!((start.line, start.column), (1, 9));
+    assert_eq!((end.line, end.column), (1, 17));
+
+    assert_eq!(
+        diagnostics[1].code.as_deref(),
+        Some("NEXT_RSC_ERR_SERVER_IMPORT")
+    );
+    let start = diagnostics[1].start.unwrap();
+    assert_eq!((start.line, start.column), (2, 0));
+}
+
+#[test]
+fn should_use_configured_deny_lists() {
+    let options: Options = serde_json::from_value(serde_json::json!({
+        "isServer": true,
+        "invalidServerImports": {
+            "replace": ["client-only"],
+            "extend": [{
+                "name": "analytics",
+                "message": "`analytics` only works in the browser."
+            }]
+        },
+        "invalidServerApis": {
+            "react": { "replace": ["useState"] },
+            "jotai": { "extend": ["useAtom"] }
+        }
+    }))
+    .unwrap();
+    let diagnostics = diagnose(
+        r#"import { useState, useEffect } from 'react'
+import { useAtom } from 'jotai'
+import 'react-dom/server'
+import 'analytics'
+"#,
+        options,
+    );
+
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "NEXT_RSC_ERR_REACT_API: useState",
+            "NEXT_RSC_ERR_CUSTOM: jotai\n`useAtom` from \"jotai\" only works in Client \
+             Components. Add the \"use client\" directive at the top of the file to use it.",
+            "NEXT_RSC_ERR_CUSTOM: analytics\n`analytics` only works in the browser.",
+        ]
+    );
+}
+
+#[test]
+fn should_extend_built_in_deny_lists() {
+    let diagnostics = diagnose(
+        "import 'legacy-client-lib'\nimport 'client-only'\n",
+        Options {
+            is_server: true,
+            invalid_server_imports: DenyList {
+                replace: None,
+                extend: vec![DenyListEntry::Name("legacy-client-lib".into())],
+            },
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].message,
+        "NEXT_RSC_ERR_SERVER_IMPORT: legacy-client-lib"
+    );
+    assert_eq!(
+        diagnostics[1].message,
+        "NEXT_RSC_ERR_SERVER_IMPORT: client-only"
+    );
+}
+
+#[test]
+fn should_report_misplaced_client_directives() {
+    let diagnostics = diagnose(
+        r#"import { Button } from './button'
+"use client"
+
+export default function Page() {
+  "use client"
+  return null
+}
+"#,
+        Options {
+            is_server: true,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics[0]
+        .message
+        .starts_with("The \"use client\" directive has no effect inside a function."));
+    assert_eq!(diagnostics[0].start.unwrap().line, 5);
+    assert!(diagnostics[1]
+        .message
+        .starts_with("The \"use client\" directive must be placed before imports"));
+    assert_eq!(diagnostics[1].start.unwrap().line, 2);
+}
+
+#[test]
+fn should_report_malformed_client_directives() {
+    for directive in ["use-client", "Use Client", "useClient", "use client "] {
+        let diagnostics = diagnose(
+            &format!("'{}'\nexport default function Page() {{}}\n", directive),
+            Options {
+                is_server: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            format!(
+                "\"{}\" is not a valid directive. Did you mean \"use client\"?",
+                directive
+            )
+        );
+    }
+}
+
+#[test]
+fn should_report_non_serializable_client_component_props() {
+    let diagnostics = diagnose(
+        r#"import { Button } from './button'
+import * as ui from '@acme/ui'
+
+async function save() {
+  "use server"
+}
+function format() {}
+
+export default function Page() {
+  return (
+    <>
+      <Button onClick={() => {}} action={save} label="Save" />
+      <ui.Dialog style={{ width: 100 }} formatter={format} />
+      <Button options={{ date: new Date(), tags: new Set(['a']), url: new URL('/') }} />
+    </>
+  )
+}
+"#,
+        Options {
+            is_server: true,
+            client_modules: vec!["/some-project/src/button.js".into(), "@acme/ui".into()],
+            ..Default::default()
+        },
+    );
+
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            let start = d.start.unwrap();
+            (d.message.as_str(), start.line, start.column)
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "The `onClick` prop of `<Button>` is a function, which can't be passed from a \
+                 Server Component to a Client Component. Only plain objects, arrays, primitives \
+                 and Server Actions can be passed.",
+                12,
+                23
+            ),
+            (
+                "The `formatter` prop of `<ui.Dialog>` is a function, which can't be passed from \
+                 a Server Component to a Client Component. Only plain objects, arrays, primitives \
+                 and Server Actions can be passed.",
+                13,
+                51
+            ),
+            (
+                "The `options` prop of `<Button>` is a class instance, which can't be passed from \
+                 a Server Component to a Client Component. Only plain objects, arrays, primitives \
+                 and Server Actions can be passed.",
+                14,
+                70
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_report_react_apis_used_through_namespaces() {
+    let diagnostics = diagnose(
+        r#"import React from 'react'
+import * as ReactDOM from 'react-dom'
+
+export default function Page() {
+  const [state] = React.useState(0)
+  const { useEffect, use } = React
+  ReactDOM['flushSync'](() => {})
+  return React.createElement('div', null, state)
+}
+"#,
+        Options {
+            is_server: true,
+            ..Default::default()
+        },
+    );
+
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            let start = d.start.unwrap();
+            (
+                d.message.as_str(),
+                d.code.as_deref(),
+                start.line,
+                start.column,
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                "NEXT_RSC_ERR_REACT_API: useState",
+                Some("NEXT_RSC_ERR_REACT_API"),
+                5,
+                18
+            ),
+            (
+                "NEXT_RSC_ERR_REACT_API: useEffect",
+                Some("NEXT_RSC_ERR_REACT_API"),
+                6,
+                10
+            ),
+            (
+                "NEXT_RSC_ERR_REACT_API: flushSync",
+                Some("NEXT_RSC_ERR_REACT_API"),
+                7,
+                2
+            ),
+        ]
+    );
+}
+
+#[test]
+fn should_report_async_client_components() {
+    let diagnostics = diagnose(
+        r#""use client"
+
+async function Avatar() {}
+export async function Profile() {
+  return <Avatar />
+}
+export const Settings = async () => null
+export async function fetchUser() {}
+export default async function () {
+  return <div />
+}
+"#,
+        Options {
+            is_server: true,
+            ..Default::default()
+        },
+    );
+
+    let errors: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            let start = d.start.unwrap();
+            (d.message.as_str(), start.line, start.column)
+        })
+        .collect();
+    let message = |component: &str| {
+        format!(
+            "{} is an async Client Component. Only Server Components can be async. Remove `async` \
+             from the component, or remove the \"use client\" directive at the top of the file.",
+            component
+        )
+    };
+    assert_eq!(
+        errors,
+        vec![
+            (message("`Profile`").as_str(), 4, 22),
+            (message("`Settings`").as_str(), 7, 13),
+            (message("The default export").as_str(), 9, 15),
+            (message("`Avatar`").as_str(), 5, 10),
+        ]
+    );
+}
@@ -42,13 +42,42 @@ fn next_dynamic_errors(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
     test_fixture(
         syntax(),
-        &|_tr| {
+        &|tr| {
             next_dynamic(
+                tr.cm.clone(),
                 true,
                 false,
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
+                None,
+            )
+        },
+        &input,
+        &output,
+        FixtureTestConfig {
+            allow_error: true,
+            ..Default::default()
+        },
+    );
+}
+
+#[fixture("tests/errors/next-dynamic-production/**/input.js")]
+fn next_dynamic_production_errors(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|tr| {
+            next_dynamic(
+                tr.cm.clone(),
+                false,
+                false,
+                false,
+                FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
+                Some("/some-project/src".into()),
+                Default::default(),
+                None,
             )
         },
         &input,
@@ -84,9 +113,13 @@ fn react_server_components_server_graph_errors(input: PathBuf) {
             server_components(
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 next_swc::react_server_components::Config::WithOptions(
-                    next_swc::react_server_components::Options { is_server: true },
+                    next_swc::react_server_components::Options {
+                        is_server: true,
+                        ..Default::default()
+                    },
                 ),
                 tr.comments.as_ref().clone(),
+                None,
             )
         },
         &input,
@@ -107,9 +140,13 @@ fn react_server_components_client_graph_errors(input: PathBuf) {
             server_components(
                 FileName::Real(PathBuf::from("/some-project/src/page.js")),
                 next_swc::react_server_components::Config::WithOptions(
-                    next_swc::react_server_components::Options { is_server: false },
+                    next_swc::react_server_components::Options {
+                        is_server: false,
+                        ..Default::default()
+                    },
                 ),
                 tr.comments.as_ref().clone(),
+                None,
             )
         },
         &input,
@@ -127,10 +164,13 @@ fn next_font_loaders_errors(input: PathBuf) {
     test_fixture(
         syntax(),
         &|_tr| {
-            next_font_loaders(FontLoaderConfig {
-                relative_file_path_from_root: "pages/test.tsx".into(),
-                font_loaders: vec!["@next/font/google".into(), "cool-fonts".into()],
-            })
+            next_font_loaders(
+                FontLoaderConfig {
+                    relative_file_path_from_root: "pages/test.tsx".into(),
+                    font_loaders: vec!["@next/font/google".into(), "cool-fonts".into()],
+                },
+                None,
+            )
         },
         &input,
         &output,
@@ -0,0 +1,6 @@
+import dynamic from 'next/dynamic'
+
+export default function Page() {
+  const Hello = dynamic(() => import('../components/hello'))
+  return Hello
+}
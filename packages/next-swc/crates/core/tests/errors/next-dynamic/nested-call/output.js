@@ -0,0 +1,12 @@
+import dynamic from 'next/dynamic';
+export default function Page() {
+    const Hello = dynamic(()=>import('../components/hello')
+    , {
+        loadableGenerated: {
+            modules: [
+                "some-file.js -> " + "../components/hello"
+            ]
+        }
+    });
+    return Hello;
+}
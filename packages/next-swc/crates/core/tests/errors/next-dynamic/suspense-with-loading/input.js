@@ -0,0 +1,6 @@
+import dynamic from 'next/dynamic'
+
+const DynamicComponentWithSuspense = dynamic(
+  () => import('../components/hello'),
+  { loading: () => <p>...</p>, suspense: true }
+)
@@ -0,0 +1,11 @@
+import dynamic from 'next/dynamic';
+const DynamicComponentWithSuspense = dynamic(()=>import('../components/hello')
+, {
+    loadableGenerated: {
+        modules: [
+            "some-file.js -> " + "../components/hello"
+        ]
+    },
+    loading: ()=><p >...</p>,
+    suspense: true
+});
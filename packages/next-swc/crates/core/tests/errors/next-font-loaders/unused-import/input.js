@@ -0,0 +1,3 @@
+import { Inter, Roboto } from '@next/font/google'
+
+const inter = Inter({ variant: '400' })
@@ -1,6 +1,9 @@
 use next_swc::{
     amp_attributes::amp_attributes,
-    next_dynamic::next_dynamic,
+    module_format::module_format,
+    next_dynamic::{
+        next_dynamic, Bundler, Config as NextDynamicConfig, DynamicFunction, NestedCalls,
+    },
     next_font_loaders::{next_font_loaders, Config as FontLoaderConfig},
     next_ssg::next_ssg,
     page_config::page_config_test,
@@ -14,8 +17,9 @@ use std::path::PathBuf;
 use swc_core::{
     common::{chain, comments::SingleThreadedComments, FileName, Mark},
     ecma::parser::{EsConfig, Syntax},
+    ecma::transforms::base::resolver,
     ecma::transforms::react::jsx,
-    ecma::transforms::testing::{test, test_fixture},
+    ecma::transforms::testing::{test, test_fixture, FixtureTestConfig},
 };
 use testing::fixture;
 
@@ -45,51 +49,176 @@ fn next_dynamic_fixture(input: PathBuf) {
     let output_server = input.parent().unwrap().join("output-server.js");
     test_fixture(
         syntax(),
-        &|_tr| {
+        &|tr| {
             next_dynamic(
+                tr.cm.clone(),
                 true,
                 false,
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
+                None,
             )
         },
         &input,
         &output_dev,
-        Default::default(),
+        FixtureTestConfig {
+            allow_error: output_dev.with_extension("stderr").exists(),
+            ..Default::default()
+        },
     );
     test_fixture(
         syntax(),
-        &|_tr| {
+        &|tr| {
             next_dynamic(
+                tr.cm.clone(),
                 false,
                 false,
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
+                None,
             )
         },
         &input,
         &output_prod,
-        Default::default(),
+        FixtureTestConfig {
+            allow_error: output_prod.with_extension("stderr").exists(),
+            ..Default::default()
+        },
     );
     test_fixture(
         syntax(),
-        &|_tr| {
+        &|tr| {
             next_dynamic(
+                tr.cm.clone(),
                 false,
                 true,
                 false,
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 Some("/some-project/src".into()),
+                Default::default(),
+                None,
             )
         },
         &input,
         &output_server,
+        FixtureTestConfig {
+            allow_error: output_server.with_extension("stderr").exists(),
+            ..Default::default()
+        },
+    );
+}
+
+#[fixture("tests/fixture/next-dynamic-custom/**/input.js")]
+fn next_dynamic_custom_functions_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|tr| {
+            next_dynamic(
+                tr.cm.clone(),
+                true,
+                false,
+                false,
+                FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
+                Some("/some-project/src".into()),
+                NextDynamicConfig {
+                    dynamic_functions: vec![
+                        DynamicFunction {
+                            module: "@acme/dynamic".into(),
+                            export: "default".into(),
+                        },
+                        DynamicFunction {
+                            module: "@acme/dynamic".into(),
+                            export: "lazyComponent".into(),
+                        },
+                    ],
+                    ..Default::default()
+                },
+                None,
+            )
+        },
+        &input,
+        &output,
         Default::default(),
     );
 }
 
+#[fixture("tests/fixture/next-dynamic-bundler/**/input.js")]
+fn next_dynamic_bundler_fixture(input: PathBuf) {
+    let output_module_ids = input.parent().unwrap().join("output-module-ids.js");
+    let output_custom = input.parent().unwrap().join("output-custom.js");
+    for (bundler, output) in [
+        (Bundler::ModuleIds, output_module_ids),
+        (
+            Bundler::Custom {
+                callee: "__bundler__.resolveWeak".into(),
+            },
+            output_custom,
+        ),
+    ] {
+        test_fixture(
+            syntax(),
+            &|tr| {
+                next_dynamic(
+                    tr.cm.clone(),
+                    false,
+                    false,
+                    false,
+                    FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
+                    Some("/some-project/src".into()),
+                    NextDynamicConfig {
+                        bundler: bundler.clone(),
+                        ..Default::default()
+                    },
+                    None,
+                )
+            },
+            &input,
+            &output,
+            Default::default(),
+        );
+    }
+}
+
+#[fixture("tests/fixture/next-dynamic-nested-calls/**/input.js")]
+fn next_dynamic_nested_calls_fixture(input: PathBuf) {
+    for (nested_calls, name) in [
+        (NestedCalls::Allow, "output-allow.js"),
+        (NestedCalls::Warn, "output-warn.js"),
+        (NestedCalls::Error, "output-error.js"),
+    ] {
+        let output = input.parent().unwrap().join(name);
+        test_fixture(
+            syntax(),
+            &|tr| {
+                next_dynamic(
+                    tr.cm.clone(),
+                    false,
+                    false,
+                    false,
+                    FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
+                    Some("/some-project/src".into()),
+                    NextDynamicConfig {
+                        nested_calls,
+                        ..Default::default()
+                    },
+                    None,
+                )
+            },
+            &input,
+            &output,
+            FixtureTestConfig {
+                allow_error: output.with_extension("stderr").exists(),
+                ..Default::default()
+            },
+        );
+    }
+}
+
 #[fixture("tests/fixture/ssg/**/input.js")]
 fn next_ssg_fixture(input: PathBuf) {
     let output = input.parent().unwrap().join("output.js");
@@ -130,7 +259,10 @@ fn page_config_fixture(input: PathBuf) {
         &|_tr| page_config_test(),
         &input,
         &output,
-        Default::default(),
+        FixtureTestConfig {
+            allow_error: output.with_extension("stderr").exists(),
+            ..Default::default()
+        },
     );
 }
 
@@ -153,7 +285,10 @@ fn relay_no_artifact_dir_fixture(input: PathBuf) {
         },
         &input,
         &output,
-        Default::default(),
+        FixtureTestConfig {
+            allow_error: output.with_extension("stderr").exists(),
+            ..Default::default()
+        },
     );
 }
 
@@ -246,9 +381,38 @@ fn react_server_components_server_graph_fixture(input: PathBuf) {
             server_components(
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 next_swc::react_server_components::Config::WithOptions(
-                    next_swc::react_server_components::Options { is_server: true },
+                    next_swc::react_server_components::Options {
+                        is_server: true,
+                        ..Default::default()
+                    },
                 ),
                 tr.comments.as_ref().clone(),
+                None,
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
+
+#[fixture("tests/fixture/react-server-components/server-graph-esm/**/input.js")]
+fn react_server_components_server_graph_esm_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|tr| {
+            server_components(
+                FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
+                next_swc::react_server_components::Config::WithOptions(
+                    next_swc::react_server_components::Options {
+                        is_server: true,
+                        esm_client_references: true,
+                        ..Default::default()
+                    },
+                ),
+                tr.comments.as_ref().clone(),
+                None,
             )
         },
         &input,
@@ -266,14 +430,21 @@ fn react_server_components_client_graph_fixture(input: PathBuf) {
             server_components(
                 FileName::Real(PathBuf::from("/some-project/src/some-file.js")),
                 next_swc::react_server_components::Config::WithOptions(
-                    next_swc::react_server_components::Options { is_server: false },
+                    next_swc::react_server_components::Options {
+                        is_server: false,
+                        ..Default::default()
+                    },
                 ),
                 tr.comments.as_ref().clone(),
+                None,
             )
         },
         &input,
         &output,
-        Default::default(),
+        FixtureTestConfig {
+            allow_error: output.with_extension("stderr").exists(),
+            ..Default::default()
+        },
     );
 }
 
@@ -283,13 +454,37 @@ fn next_font_loaders_fixture(input: PathBuf) {
     test_fixture(
         syntax(),
         &|_tr| {
-            next_font_loaders(FontLoaderConfig {
-                relative_file_path_from_root: "pages/test.tsx".into(),
-                font_loaders: vec!["@next/font/google".into(), "cool-fonts".into()],
-            })
+            next_font_loaders(
+                FontLoaderConfig {
+                    relative_file_path_from_root: "pages/test.tsx".into(),
+                    font_loaders: vec!["@next/font/google".into(), "cool-fonts".into()],
+                },
+                None,
+            )
         },
         &input,
         &output,
         Default::default(),
     );
 }
+
+#[fixture("tests/fixture/module-format/**/input.js")]
+fn module_format_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        syntax(),
+        &|_tr| {
+            let unresolved_mark = Mark::new();
+            chain!(
+                resolver(unresolved_mark, Mark::new(), false),
+                module_format(unresolved_mark, Default::default())
+            )
+        },
+        &input,
+        &output,
+        FixtureTestConfig {
+            allow_error: output.with_extension("stderr").exists(),
+            ..Default::default()
+        },
+    );
+}
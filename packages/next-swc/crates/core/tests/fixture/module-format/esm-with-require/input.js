@@ -0,0 +1,5 @@
+const config = require('./config.json')
+
+export default function Page() {
+  return config.title
+}
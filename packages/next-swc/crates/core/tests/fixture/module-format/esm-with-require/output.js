@@ -0,0 +1,4 @@
+const config = require('./config.json');
+export default function Page() {
+    return config.title;
+}
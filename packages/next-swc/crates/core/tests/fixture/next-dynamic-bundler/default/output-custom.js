@@ -0,0 +1,8 @@
+import dynamic from 'next/dynamic';
+const DynamicComponent = dynamic(()=>import('../components/hello'), {
+    loadableGenerated: {
+        webpack: ()=>[
+                __bundler__.resolveWeak("../components/hello")
+            ]
+    }
+});
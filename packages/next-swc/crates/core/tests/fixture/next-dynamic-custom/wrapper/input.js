@@ -0,0 +1,6 @@
+import lazy, { lazyComponent } from '@acme/dynamic'
+import { dynamic } from '@acme/other'
+
+const Hello = lazyComponent(() => import('../components/hello'))
+const World = lazy(() => import('../components/world'))
+const Ignored = dynamic(() => import('../components/ignored'))
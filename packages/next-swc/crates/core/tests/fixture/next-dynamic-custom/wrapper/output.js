@@ -0,0 +1,17 @@
+import lazy, { lazyComponent } from '@acme/dynamic';
+import { dynamic } from '@acme/other';
+const Hello = lazyComponent(()=>import('../components/hello'), {
+    loadableGenerated: {
+        modules: [
+            "some-file.js -> " + "../components/hello"
+        ]
+    }
+});
+const World = lazy(()=>import('../components/world'), {
+    loadableGenerated: {
+        modules: [
+            "some-file.js -> " + "../components/world"
+        ]
+    }
+});
+const Ignored = dynamic(()=>import('../components/ignored'));
@@ -0,0 +1,9 @@
+import dynamic from 'next/dynamic'
+
+const components = {
+  get Hello() {
+    return dynamic(() => import('../components/hello'))
+  },
+}
+
+export default components
@@ -0,0 +1,14 @@
+import dynamic from 'next/dynamic';
+const components = {
+    get Hello () {
+        return dynamic(()=>import('../components/hello')
+        , {
+            loadableGenerated: {
+                webpack: ()=>[
+                        require.resolveWeak("../components/hello")
+                    ]
+            }
+        });
+    }
+};
+export default components;
@@ -0,0 +1,3 @@
+import { default as dynamic } from 'next/dynamic'
+
+const DynamicComponent = dynamic(() => import('../components/hello'))
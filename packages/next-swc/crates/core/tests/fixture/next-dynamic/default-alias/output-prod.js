@@ -0,0 +1,8 @@
+import { default as dynamic } from 'next/dynamic';
+const DynamicComponent = dynamic(()=>import('../components/hello'), {
+    loadableGenerated: {
+        webpack: ()=>[
+                require.resolveWeak("../components/hello")
+            ]
+    }
+});
@@ -0,0 +1,3 @@
+import dynamic from 'next/dynamic'
+
+const Chained = dynamic(() => import('../components/a').then(() => import('../components/b')))
@@ -0,0 +1,9 @@
+import dynamic from 'next/dynamic';
+const Chained = dynamic(()=>import('../components/a').then(()=>import('../components/b')), {
+    loadableGenerated: {
+        webpack: ()=>[
+                require.resolveWeak("../components/a"),
+                require.resolveWeak("../components/b")
+            ]
+    }
+});
@@ -0,0 +1,9 @@
+import dynamic from 'next/dynamic';
+const Chained = dynamic(()=>import('../components/a').then(()=>import('../components/b')), {
+    loadableGenerated: {
+        modules: [
+            "some-file.js -> " + "../components/a",
+            "some-file.js -> " + "../components/b"
+        ]
+    }
+});
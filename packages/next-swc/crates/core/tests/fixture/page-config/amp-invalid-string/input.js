@@ -0,0 +1,5 @@
+export const config = { amp: 'yes' }
+
+export default function Page() {
+  return <h3>My Page</h3>
+}
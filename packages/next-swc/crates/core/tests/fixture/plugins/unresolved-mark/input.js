@@ -0,0 +1,5 @@
+console.log(__PLUGIN__)
+
+export function withBinding(__PLUGIN__) {
+  return __PLUGIN__
+}
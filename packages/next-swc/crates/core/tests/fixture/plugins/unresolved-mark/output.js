@@ -0,0 +1,4 @@
+console.log(__renamed_by_plugin__);
+export function withBinding(__PLUGIN__) {
+    return __PLUGIN__;
+}
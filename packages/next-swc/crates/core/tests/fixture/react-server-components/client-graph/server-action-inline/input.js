@@ -0,0 +1,15 @@
+import { db } from "./db";
+
+export default function Post({ id }) {
+  async function like(formData) {
+    "use server";
+    await db.posts.like(formData.get("id"));
+  }
+
+  const unlike = async (formData) => {
+    "use server";
+    await db.posts.unlike(formData.get("id"));
+  };
+
+  return <form action={like}><input type="hidden" name="id" value={id} /><button formAction={unlike} /></form>;
+}
@@ -0,0 +1,9 @@
+import { createActionProxy as $$createActionProxy } from "private-next-rsc-action-proxy";
+import { db } from "./db";
+const $$INLINE_ACTION_0 = $$createActionProxy("28706d1ae13cb1cc4b46f3b36a5808cf85bc606c");
+const $$INLINE_ACTION_1 = $$createActionProxy("229c3c70034e1d1c96b98f4f1cdd16450bcfd1ab");
+export default function Post({ id  }) {
+    const like = $$INLINE_ACTION_0;
+    const unlike = $$INLINE_ACTION_1;
+    return <form action={like}><input type="hidden" name="id" value={id}/><button formAction={unlike}/></form>;
+}
@@ -0,0 +1,6 @@
+import { createActionProxy } from "private-next-rsc-action-proxy";
+const $$ACTION_0 = createActionProxy("86a9739b66b5d22086177a7745239f6b4e2c29e2");
+const $$ACTION_1 = createActionProxy("bfe9aa8019bb5b9f0eaadbfbb182fe68200e157d");
+const $$ACTION_2 = createActionProxy("568701219d5d1a29c3b3de3f848f49e6a290930a");
+const $$ACTION_3 = createActionProxy("7e7e56e9623ed521d93208bc5367272f291029da");
+export { $$ACTION_0 as createTodo, $$ACTION_1 as deleteTodo, $$ACTION_2 as renameTodo, $$ACTION_3 as default };
@@ -0,0 +1,6 @@
+"use client";
+
+export default async () => {
+  const res = await fetch("/api/user");
+  return res.json();
+};
@@ -0,0 +1,3 @@
+/* __next_internal_client_entry_do_not_use__ ["default"] */ import { createReference } from "private-next-rsc-mod-ref-proxy";
+const $$REF_0 = createReference("/some-project/src/some-file.js", "default");
+export { $$REF_0 as default };
@@ -0,0 +1,10 @@
+"use client";
+
+export async function fetchUser() {
+  return null;
+}
+
+export default async function fetchData() {
+  const user = await fetchUser();
+  return () => <div>{user}</div>;
+}
@@ -0,0 +1,9 @@
+"use client";
+
+const messages = await import("./messages.js");
+
+export async function load() {
+  return await messages.load();
+}
+
+export default messages;
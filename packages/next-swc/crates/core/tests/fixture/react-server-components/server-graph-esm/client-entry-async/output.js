@@ -0,0 +1,4 @@
+/* __next_internal_client_entry_do_not_use__ ["load","default"] */ import { createReference } from "private-next-rsc-mod-ref-proxy";
+const $$REF_0 = createReference("/some-project/src/some-file.js", "load", true);
+const $$REF_1 = createReference("/some-project/src/some-file.js", "default", true);
+export { $$REF_0 as load, $$REF_1 as default };
@@ -0,0 +1,7 @@
+"use client";
+
+function Button() {
+  return null;
+}
+
+export { Button as "Save, Cancel", Button as "*/ alert(1) /*" };
@@ -0,0 +1,4 @@
+/* __next_internal_client_entry_do_not_use__ ["Save, Cancel","*\/ alert(1) /*"] */ import { createReference } from "private-next-rsc-mod-ref-proxy";
+const $$REF_0 = createReference("/some-project/src/some-file.js", "Save, Cancel");
+const $$REF_1 = createReference("/some-project/src/some-file.js", "*/ alert(1) /*");
+export { $$REF_0 as "Save, Cancel", $$REF_1 as "*/ alert(1) /*" };
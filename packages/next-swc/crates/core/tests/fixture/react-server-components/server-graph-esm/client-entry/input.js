@@ -0,0 +1,18 @@
+"use client";
+
+import { useState } from "react";
+
+export function Counter() {
+  const [count] = useState(0);
+  return count;
+}
+
+export const Title = () => null;
+
+function Button() {
+  return null;
+}
+
+export { Button as "my-button" };
+
+export default Counter;
@@ -0,0 +1,6 @@
+/* __next_internal_client_entry_do_not_use__ ["Counter","Title","my-button","default"] */ import { createReference } from "private-next-rsc-mod-ref-proxy";
+const $$REF_0 = createReference("/some-project/src/some-file.js", "Counter");
+const $$REF_1 = createReference("/some-project/src/some-file.js", "Title");
+const $$REF_2 = createReference("/some-project/src/some-file.js", "my-button");
+const $$REF_3 = createReference("/some-project/src/some-file.js", "default");
+export { $$REF_0 as Counter, $$REF_1 as Title, $$REF_2 as "my-button", $$REF_3 as default };
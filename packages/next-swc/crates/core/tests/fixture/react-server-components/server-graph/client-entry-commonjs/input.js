@@ -0,0 +1,8 @@
+"use client";
+
+const { useState } = require("react");
+
+module.exports = function Counter() {
+  const [count] = useState(0);
+  return count;
+};
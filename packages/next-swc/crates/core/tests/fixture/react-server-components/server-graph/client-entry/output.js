@@ -1,3 +1,6 @@
 // This is a comment.
-/* __next_internal_client_entry_do_not_use__ */ const { createProxy  } = require("private-next-rsc-mod-ref-proxy");
-module.exports = createProxy("/some-project/src/some-file.js");
+/* __next_internal_client_entry_do_not_use__ ["default"] */ const { createReference  } = require("private-next-rsc-mod-ref-proxy");
+module.exports = {
+    __esModule: true,
+    default: createReference("/some-project/src/some-file.js", "default")
+};
@@ -0,0 +1,15 @@
+import { db } from "./db";
+
+async function like(id) {
+  "use server";
+  await db.posts.like(id);
+}
+
+export const unlike = async (id) => {
+  "use server";
+  await db.posts.unlike(id);
+};
+
+export default function Post({ id }) {
+  return <form action={like}><input type="hidden" name="id" value={id} /></form>;
+}
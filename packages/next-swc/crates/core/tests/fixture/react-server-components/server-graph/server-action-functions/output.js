@@ -0,0 +1,15 @@
+import { registerServerAction as $$registerServerAction } from "private-next-rsc-action-registry";
+import { db } from "./db";
+async function like(id) {
+    "use server";
+    await db.posts.like(id);
+}
+export const unlike = async (id)=>{
+    "use server";
+    await db.posts.unlike(id);
+};
+export default function Post({ id  }) {
+    return <form action={like}><input type="hidden" name="id" value={id}/></form>;
+}
+$$registerServerAction("c683e269f103b743e577bb1d4c434b87d9a4e9a7", like);
+$$registerServerAction("1075c425d6cb6399f1ddd38cbc244681c1fd1462", unlike);
@@ -0,0 +1,17 @@
+import { registerServerAction as $$registerServerAction } from "private-next-rsc-action-registry";
+import { db } from "./db";
+async function $$INLINE_ACTION_0(formData) {
+    "use server";
+    await db.posts.like(formData.get("id"));
+}
+const $$INLINE_ACTION_1 = async (formData)=>{
+    "use server";
+    await db.posts.unlike(formData.get("id"));
+};
+export default function Post({ id  }) {
+    const like = $$INLINE_ACTION_0;
+    const unlike = $$INLINE_ACTION_1;
+    return <form action={like}><input type="hidden" name="id" value={id}/><button formAction={unlike}/></form>;
+}
+$$registerServerAction("28706d1ae13cb1cc4b46f3b36a5808cf85bc606c", $$INLINE_ACTION_0);
+$$registerServerAction("229c3c70034e1d1c96b98f4f1cdd16450bcfd1ab", $$INLINE_ACTION_1);
@@ -0,0 +1,21 @@
+"use server";
+
+import { db } from "./db";
+
+export async function createTodo(title) {
+  await db.todos.create({ title });
+}
+
+export const deleteTodo = async (id) => {
+  await db.todos.delete(id);
+};
+
+async function updateTodo(id, title) {
+  await db.todos.update(id, { title });
+}
+
+export { updateTodo as renameTodo };
+
+export default async function () {
+  await db.todos.clear();
+}
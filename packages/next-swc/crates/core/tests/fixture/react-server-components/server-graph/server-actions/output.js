@@ -0,0 +1,23 @@
+import { registerServerAction as $$registerServerAction } from "private-next-rsc-action-registry";
+import { db } from "./db";
+export async function createTodo(title) {
+    await db.todos.create({
+        title
+    });
+}
+export const deleteTodo = async (id)=>{
+    await db.todos.delete(id);
+};
+async function updateTodo(id, title) {
+    await db.todos.update(id, {
+        title
+    });
+}
+export { updateTodo as renameTodo };
+export default async function $$ACTION_DEFAULT() {
+    await db.todos.clear();
+}
+$$registerServerAction("86a9739b66b5d22086177a7745239f6b4e2c29e2", createTodo);
+$$registerServerAction("bfe9aa8019bb5b9f0eaadbfbb182fe68200e157d", deleteTodo);
+$$registerServerAction("568701219d5d1a29c3b3de3f848f49e6a290930a", updateTodo);
+$$registerServerAction("7e7e56e9623ed521d93208bc5367272f291029da", $$ACTION_DEFAULT);
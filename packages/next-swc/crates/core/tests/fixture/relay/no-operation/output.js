@@ -0,0 +1 @@
+const anonymousQuery = graphql`{ hello }`;
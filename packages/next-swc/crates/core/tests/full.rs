@@ -3,7 +3,7 @@ use serde::de::DeserializeOwned;
 use std::path::{Path, PathBuf};
 use swc_core::{
     base::Compiler,
-    common::comments::SingleThreadedComments,
+    common::{comments::SingleThreadedComments, Mark},
     ecma::parser::{Syntax, TsConfig},
     ecma::transforms::base::pass::noop,
 };
@@ -27,11 +27,13 @@ fn test(input: &Path, minify: bool) {
             let c = Compiler::new(cm.clone());
 
             let fm = cm.load_file(input).expect("failed to load file");
+            let unresolved_mark = Mark::new();
 
             let options = TransformOptions {
                 swc: swc_core::base::config::Options {
                     swcrc: true,
                     output_path: Some(output.clone()),
+                    unresolved_mark: Some(unresolved_mark),
 
                     config: swc_core::base::config::Config {
                         is_module: swc_core::base::config::IsModule::Bool(true),
@@ -68,6 +70,12 @@ fn test(input: &Path, minify: bool) {
                 emotion: Some(assert_json("{}")),
                 modularize_imports: None,
                 font_loaders: None,
+                next_dynamic: None,
+                bundler: Default::default(),
+                collect_pass_stats: false,
+                collect_metadata: false,
+                strict_options: false,
+                plugins: vec![],
             };
 
             let options = options.patch(&fm);
@@ -86,6 +94,7 @@ fn test(input: &Path, minify: bool) {
                         &options,
                         comments.clone(),
                         Default::default(),
+                        unresolved_mark,
                     )
                 },
                 |_| noop(),
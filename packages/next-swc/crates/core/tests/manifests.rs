@@ -0,0 +1,238 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use next_swc::{
+    diagnostics::Location,
+    manifests::{
+        merge_client_reference_fragments, merge_loadable_fragments, CallSite, ChunkingHints,
+        ClientReference, ClientReferenceFragment, LoadableFragment,
+    },
+    metadata::ModuleMetadata,
+    next_dynamic::next_dynamic,
+    react_server_components::Options,
+};
+use serde_json::json;
+
+use swc_core::{
+    base::try_with_handler,
+    common::{comments::SingleThreadedComments, FileName, GLOBALS},
+    ecma::transforms::base::pass::noop,
+};
+
+use crate::common::{transform_server_components, COMPILER};
+
+mod common;
+
+fn server_metadata(filename: &str, src: &str) -> ModuleMetadata {
+    let metadata: Rc<RefCell<ModuleMetadata>> = Default::default();
+    let (ok, _) = transform_server_components(
+        filename,
+        src,
+        &Default::default(),
+        Options {
+            is_server: true,
+            ..Default::default()
+        },
+        Some(metadata.clone()),
+    );
+
+    assert!(ok);
+    metadata.take()
+}
+
+#[test]
+fn should_record_client_reference_fragments() {
+    let metadata = server_metadata(
+        "/some-project/src/counter.js",
+        r#""use client"
+import './counter.css'
+import styles from '../styles/button.module.css'
+import 'some-ui/theme.css'
+
+const config = await fetch('/config')
+
+export function Counter() {}
+export default async function Button() {
+  await null
+}
+"#,
+    );
+
+    assert_eq!(
+        metadata.client_reference,
+        Some(ClientReferenceFragment {
+            module_path: "/some-project/src/counter.js".into(),
+            exports: vec!["Counter".into(), "default".into()],
+            chunking: ChunkingHints {
+                is_async: true,
+                css_imports: vec![
+                    "/some-project/src/counter.css".into(),
+                    "/some-project/styles/button.module.css".into(),
+                    "some-ui/theme.css".into(),
+                ],
+            },
+        })
+    );
+
+    let metadata = server_metadata(
+        "/some-project/src/reexports.js",
+        "\"use client\"\nexport * from './counter'\n",
+    );
+    let fragment = metadata.client_reference.unwrap();
+    assert_eq!(fragment.exports, vec!["*"]);
+    assert!(!fragment.chunking.is_async);
+
+    let metadata = server_metadata(
+        "/some-project/src/page.js",
+        "export default function Page() {}\n",
+    );
+    assert_eq!(metadata.client_reference, None);
+}
+
+#[test]
+fn should_merge_client_reference_fragments() {
+    let fragment = |exports: &[&str], is_async, css: &[&str]| ClientReferenceFragment {
+        module_path: "/app/counter.js".into(),
+        exports: exports.iter().map(|e| e.to_string()).collect(),
+        chunking: ChunkingHints {
+            is_async,
+            css_imports: css.iter().map(|c| c.to_string()).collect(),
+        },
+    };
+    let manifest = merge_client_reference_fragments(vec![
+        fragment(&["Counter"], false, &["./counter.css"]),
+        fragment(
+            &["Counter", "default"],
+            true,
+            &["./counter.css", "./theme.css"],
+        ),
+    ]);
+
+    let counter = &manifest.client_modules["/app/counter.js"];
+    assert_eq!(counter.len(), 2);
+    assert_eq!(
+        counter["Counter"],
+        ClientReference {
+            id: "/app/counter.js".into(),
+            name: "Counter".into(),
+            chunks: vec![],
+            r#async: true,
+        }
+    );
+    assert_eq!(
+        manifest.css_files["/app/counter.js"],
+        vec!["./counter.css", "./theme.css"]
+    );
+    assert_eq!(
+        serde_json::to_value(&counter["default"]).unwrap(),
+        json!({ "id": "/app/counter.js", "name": "default", "chunks": [], "async": true })
+    );
+}
+
+fn loadable_modules(src: &str) -> Vec<LoadableFragment> {
+    let fm = COMPILER.cm.new_source_file(
+        FileName::Real("/some-project/src/pages/index.js".into()),
+        src.to_owned(),
+    );
+
+    let metadata: Rc<RefCell<ModuleMetadata>> = Default::default();
+    try_with_handler(COMPILER.cm.clone(), Default::default(), |handler| {
+        GLOBALS.set(&Default::default(), || {
+            COMPILER.process_js_with_custom_pass(
+                fm.clone(),
+                None,
+                handler,
+                &Default::default(),
+                SingleThreadedComments::default(),
+                |_| {
+                    next_dynamic(
+                        COMPILER.cm.clone(),
+                        false,
+                        false,
+                        false,
+                        fm.name.clone(),
+                        Some(PathBuf::from("/some-project/src/pages")),
+                        Default::default(),
+                        Some(metadata.clone()),
+                    )
+                },
+                |_| noop(),
+            )
+        })
+    })
+    .unwrap();
+
+    metadata.take().loadable_modules
+}
+
+#[test]
+fn should_record_loadable_fragments() {
+    let fragments = loadable_modules(
+        r#"import dynamic from 'next/dynamic'
+
+const Hello = dynamic(() => import('../components/hello'))
+const Chart = dynamic(() => import('../components/chart'), { ssr: false, suspense: true })
+"#,
+    );
+
+    let call_site = |line, end| CallSite {
+        file: "/some-project/src/pages/index.js".into(),
+        start: Location { line, column: 14 },
+        end: Location { line, column: end },
+    };
+    assert_eq!(
+        fragments,
+        vec![
+            LoadableFragment {
+                key: "index.js -> ../components/hello".into(),
+                specifier: "../components/hello".into(),
+                ssr: true,
+                suspense: false,
+                call_site: call_site(3, 58),
+            },
+            LoadableFragment {
+                key: "index.js -> ../components/chart".into(),
+                specifier: "../components/chart".into(),
+                ssr: false,
+                suspense: true,
+                call_site: call_site(4, 90),
+            },
+        ]
+    );
+}
+
+#[test]
+fn should_merge_loadable_fragments() {
+    let fragment = |file: &str, ssr| LoadableFragment {
+        key: "index.js -> ../components/hello".into(),
+        specifier: "../components/hello".into(),
+        ssr,
+        suspense: false,
+        call_site: CallSite {
+            file: file.into(),
+            start: Location { line: 1, column: 0 },
+            end: Location {
+                line: 1,
+                column: 10,
+            },
+        },
+    };
+    let manifest = merge_loadable_fragments(vec![
+        fragment("/pages/index.js", false),
+        fragment("/pages/index.js", true),
+        fragment("/pages/index.tsx", false),
+    ]);
+
+    assert_eq!(manifest.len(), 1);
+    let entry = &manifest["index.js -> ../components/hello"];
+    assert_eq!(entry.id, "../components/hello");
+    assert!(entry.ssr);
+    assert_eq!(entry.call_sites.len(), 2);
+    assert_eq!(
+        serde_json::to_value(entry).unwrap()["callSites"][0],
+        json!({
+            "file": "/pages/index.js",
+            "start": { "line": 1, "column": 0 },
+            "end": { "line": 1, "column": 10 },
+        })
+    );
+}
@@ -0,0 +1,69 @@
+use next_swc::metadata::ModuleMetadata;
+
+use crate::common::transform_with_custom_pass;
+
+mod common;
+
+fn collect(src: &str) -> ModuleMetadata {
+    let context = transform_with_custom_pass(
+        src,
+        serde_json::json!({
+            "filename": "/some-project/pages/index.js",
+            "isPageFile": true,
+            "collectMetadata": true,
+            "fontLoaders": {
+                "fontLoaders": ["@next/font/google"],
+                "relativeFilePathFromRoot": "pages/index.js",
+            },
+        }),
+    );
+
+    context.metadata.take()
+}
+
+#[test]
+fn should_collect_module_metadata() {
+    let metadata = collect(
+        r#"import dynamic from 'next/dynamic'
+import { Inter } from '@next/font/google'
+const inter = Inter({ weight: '400' })
+const Hello = dynamic(() => import('../components/hello'))
+export const { a, b: [c] } = {}
+export { Hello as World }
+export * from './other'
+export default function Home() { return null }
+export async function getStaticProps() { return { props: {} } }
+"#,
+    );
+
+    assert_eq!(
+        metadata.exports,
+        vec!["a", "c", "World", "default", "getStaticProps"]
+    );
+    assert_eq!(metadata.star_exports, vec!["./other"]);
+    assert!(!metadata.is_client_entry);
+    assert!(metadata.has_get_static_props);
+    assert!(!metadata.has_get_server_side_props);
+    assert!(!metadata.has_get_static_paths);
+    assert_eq!(metadata.dynamic_imports, vec!["../components/hello"]);
+
+    assert_eq!(metadata.font_loader_calls.len(), 1);
+    let call = &metadata.font_loader_calls[0];
+    assert_eq!(call.loader, "@next/font/google");
+    assert_eq!(call.import, "Inter");
+    assert_eq!(call.arguments, vec![serde_json::json!({ "weight": "400" })]);
+    assert_eq!(call.variable_name.as_deref(), Some("inter"));
+}
+
+#[test]
+fn should_detect_use_client() {
+    let metadata = collect(
+        r#"'use strict'
+'use client'
+export default function Button() { return null }
+"#,
+    );
+
+    assert!(metadata.is_client_entry);
+    assert_eq!(metadata.exports, vec!["default"]);
+}
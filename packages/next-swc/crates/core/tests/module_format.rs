@@ -0,0 +1,103 @@
+use next_swc::module_format::{detect_module_format, needs_commonjs, ModuleFormat};
+use swc_core::{
+    common::{FileName, Mark, SourceMap, GLOBALS},
+    ecma::ast::{EsVersion, Program},
+    ecma::parser::parse_file_as_module,
+    ecma::transforms::base::resolver,
+    ecma::visit::FoldWith,
+};
+
+fn detect(src: &str) -> ModuleFormat {
+    analyze(src).0
+}
+
+fn analyze(src: &str) -> (ModuleFormat, bool) {
+    GLOBALS.set(&Default::default(), || {
+        let cm = SourceMap::default();
+        let fm = cm.new_source_file(FileName::Anon, src.to_owned());
+        let module = parse_file_as_module(
+            &fm,
+            Default::default(),
+            EsVersion::latest(),
+            None,
+            &mut vec![],
+        )
+        .unwrap();
+
+        let unresolved_mark = Mark::new();
+        let program =
+            Program::Module(module).fold_with(&mut resolver(unresolved_mark, Mark::new(), false));
+        (
+            detect_module_format(&program, unresolved_mark),
+            needs_commonjs(&program, unresolved_mark),
+        )
+    })
+}
+
+#[test]
+fn should_detect_module_format() {
+    assert_eq!(detect("export const a = 1"), ModuleFormat::Esm);
+    assert_eq!(detect("console.log(import.meta.url)"), ModuleFormat::Esm);
+    assert_eq!(detect("module.exports = 1"), ModuleFormat::CommonJs);
+    assert_eq!(detect("exports.foo = 1"), ModuleFormat::CommonJs);
+    assert_eq!(
+        detect("Object.defineProperty(exports, 'foo', { value: 1 })"),
+        ModuleFormat::CommonJs
+    );
+    assert_eq!(detect("const a = require('a')"), ModuleFormat::CommonJs);
+    assert_eq!(
+        detect("const a = require('a')\nexport default a"),
+        ModuleFormat::Mixed
+    );
+    assert_eq!(
+        detect("import a from 'a'\nmodule.exports = a"),
+        ModuleFormat::Mixed
+    );
+    assert_eq!(detect("console.log(1)"), ModuleFormat::Script);
+}
+
+#[test]
+fn should_ignore_shadowed_globals() {
+    assert_eq!(
+        detect("const exports = {}\nexport default exports"),
+        ModuleFormat::Esm
+    );
+    assert_eq!(
+        detect("import require from 'a'\nrequire('b')"),
+        ModuleFormat::Esm
+    );
+    assert_eq!(
+        detect("function f(exports) { exports.x = 1 }"),
+        ModuleFormat::Script
+    );
+    assert_eq!(
+        detect("define(function (require, exports, module) { module.exports = require('a') })"),
+        ModuleFormat::Script
+    );
+}
+
+#[test]
+fn should_only_need_commonjs_for_commonjs_exports() {
+    assert!(analyze("module.exports = 1").1);
+    assert!(analyze("import a from 'a'\nexports.a = a").1);
+    assert!(!analyze("export const a = 1").1);
+
+    // `require` works within ES modules.
+    assert_eq!(
+        analyze("const a = require('a')\nexport default a"),
+        (ModuleFormat::Mixed, false)
+    );
+    // UMD wrappers only use `exports` within functions.
+    assert_eq!(
+        analyze(
+            "(function (root, factory) {\n  if (typeof exports === 'object') module.exports = \
+             factory()\n  else root.lib = factory()\n})(this, function () { return {} })"
+        ),
+        (ModuleFormat::CommonJs, true)
+    );
+    // The `__esModule` flag of compiled ES modules.
+    assert_eq!(
+        analyze("(function (m) { Object.defineProperty(m, '__esModule', { value: true }) })(this)"),
+        (ModuleFormat::Script, true)
+    );
+}
@@ -0,0 +1,193 @@
+use next_swc::{
+    options_schema::{transform_options_schema, SWC_OPTIONS},
+    TransformOptions,
+};
+use serde::{
+    de::{value::Error, Error as _, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+use serde_json::json;
+
+fn error_paths(options: serde_json::Value) -> Vec<String> {
+    TransformOptions::from_json(options)
+        .unwrap_err()
+        .into_iter()
+        .map(|err| err.path)
+        .collect()
+}
+
+#[test]
+fn should_ignore_unknown_fields_by_default() {
+    assert!(TransformOptions::from_json(json!({
+        "removeConsle": true,
+        "shakeExports": { "ignore": [], "ignor": [] },
+    }))
+    .is_ok());
+}
+
+#[test]
+fn should_reject_unknown_fields_in_strict_mode() {
+    assert_eq!(
+        error_paths(json!({
+            "strictOptions": true,
+            "filename": "index.js",
+            "jsc": {},
+            "removeConsle": true,
+            "shakeExports": { "ignore": [], "ignor": [] },
+        })),
+        vec!["removeConsle", "shakeExports.ignor"]
+    );
+}
+
+#[test]
+fn should_report_the_path_of_invalid_values() {
+    let errors = TransformOptions::from_json(json!({
+        "styledComponents": { "displayName": "yes" },
+    }))
+    .unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].path, "styledComponents.displayName");
+    assert!(errors[0]
+        .to_string()
+        .starts_with("Invalid option `styledComponents.displayName`: invalid type"));
+}
+
+#[test]
+fn should_reject_invalid_bundler_callees() {
+    for callee in ["", "a-b", "a..b", "resolve()"] {
+        let errors = TransformOptions::from_json(json!({
+            "bundler": { "custom": { "callee": callee } },
+        }))
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].path.starts_with("bundler"));
+        assert!(errors[0]
+            .to_string()
+            .contains(&format!("`{}` is not a valid callee", callee)));
+    }
+
+    assert!(TransformOptions::from_json(json!({
+        "bundler": { "custom": { "callee": "__bundler__.resolveWeak" } },
+    }))
+    .is_ok());
+}
+
+#[test]
+fn should_describe_next_and_swc_options() {
+    let schema = serde_json::to_value(transform_options_schema()).unwrap();
+    let properties = schema["properties"].as_object().unwrap();
+
+    assert!(properties.contains_key("removeConsole"));
+    assert!(properties.contains_key("strictOptions"));
+    assert!(properties.contains_key("jsc"));
+    assert_eq!(properties["isServer"]["type"], "boolean");
+}
+
+#[test]
+fn should_describe_the_options_of_other_crates() {
+    let schema = serde_json::to_value(transform_options_schema()).unwrap();
+    let definition = |name: &str| &schema["definitions"][name]["properties"];
+
+    assert_eq!(
+        definition("StyledComponentsConfig")["displayName"]["type"],
+        json!(["boolean", "null"])
+    );
+    assert_eq!(
+        definition("EmotionOptions")["labelFormat"]["type"],
+        json!(["string", "null"])
+    );
+    assert_eq!(
+        definition("ModularizeImportsPackage")["transform"]["type"],
+        "string"
+    );
+}
+
+#[test]
+fn should_validate_the_options_of_other_crates_in_strict_mode() {
+    assert_eq!(
+        error_paths(json!({
+            "strictOptions": true,
+            "styledComponents": { "displayName": "yes" },
+        })),
+        vec!["styledComponents.displayName"]
+    );
+}
+
+/// Reads the names of the fields of `T` from its derived `Deserialize`, which
+/// hands them to `deserialize_struct`.
+fn upstream_fields<'de, T: Deserialize<'de>>() -> Vec<&'static str> {
+    struct FieldsProbe<'a>(&'a mut Vec<&'static str>);
+
+    impl<'de> Deserializer<'de> for FieldsProbe<'_> {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+            Err(Error::custom("expected a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Error> {
+            self.0.extend_from_slice(fields);
+            Err(Error::custom("only the fields are read"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+            identifier ignored_any
+        }
+    }
+
+    let mut fields = vec![];
+    let _ = T::deserialize(FieldsProbe(&mut fields));
+    assert!(
+        !fields.is_empty(),
+        "{} is not a struct",
+        std::any::type_name::<T>()
+    );
+    fields.sort_unstable();
+    fields
+}
+
+fn schema_fields(definition: &str) -> Vec<String> {
+    let schema = serde_json::to_value(transform_options_schema()).unwrap();
+    let mut fields: Vec<String> = schema["definitions"][definition]["properties"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect();
+    fields.sort_unstable();
+    fields
+}
+
+#[test]
+fn should_describe_every_field_of_other_crates() {
+    assert_eq!(
+        schema_fields("StyledComponentsConfig"),
+        upstream_fields::<styled_components::Config>()
+    );
+    assert_eq!(
+        schema_fields("EmotionOptions"),
+        upstream_fields::<swc_emotion::EmotionOptions>()
+    );
+    assert_eq!(
+        schema_fields("ModularizeImportsPackage"),
+        upstream_fields::<modularize_imports::PackageConfig>()
+    );
+}
+
+#[test]
+fn should_list_every_field_of_the_swc_config() {
+    // `Options` flattens `Config`, so only the fields of the latter can be
+    // read.
+    for field in upstream_fields::<swc_core::base::config::Config>() {
+        assert!(SWC_OPTIONS.contains(&field), "{} is not listed", field);
+    }
+}
@@ -0,0 +1,77 @@
+use next_swc::{
+    pass_stats::{PassStats, PassTracer},
+    remove_console::{remove_console, Config},
+    PassContext,
+};
+
+use swc_core::{
+    common::{FileName, GLOBALS},
+    ecma::parser::parse_file_as_module,
+    ecma::visit::FoldWith,
+};
+
+use crate::common::{transform_with_custom_pass, COMPILER};
+
+mod common;
+
+fn run(collect_pass_stats: bool) -> Vec<PassStats> {
+    let context = transform_with_custom_pass(
+        r#"import fs from 'fs'
+export default function Home() { return null }
+export async function getStaticProps() {
+  fs.readFileSync('data.json')
+  return { props: {} }
+}
+"#,
+        serde_json::json!({
+            "filename": "/some-project/pages/index.js",
+            "isPageFile": true,
+            "collectPassStats": collect_pass_stats,
+        }),
+    );
+
+    context.pass_stats.take()
+}
+
+#[test]
+fn should_only_collect_pass_stats_when_enabled() {
+    assert!(run(false).is_empty());
+
+    let stats = run(true);
+    let names: Vec<_> = stats.iter().map(|s| s.name).collect();
+    assert_eq!(names[0], "metadata");
+    assert!(names.contains(&"font_loaders"));
+
+    let next_ssg = stats.iter().find(|s| s.name == "next_ssg").unwrap();
+    assert!(next_ssg.nodes_after < next_ssg.nodes_before);
+    assert!(next_ssg.duration_ms.is_some());
+}
+
+#[test]
+fn should_trace_passes_started_from_module_items() {
+    let fm = COMPILER.cm.new_source_file(
+        FileName::Real("/some-project/pages/index.js".into()),
+        "console.log('a')\ntrack('b')\n".to_owned(),
+    );
+    let module = parse_file_as_module(
+        &fm,
+        Default::default(),
+        Default::default(),
+        None,
+        &mut vec![],
+    )
+    .unwrap();
+
+    let context = PassContext::default();
+    let tracer = PassTracer::new(&fm.name, Some(context.pass_stats.clone()));
+    let mut pass = tracer.wrap("remove_console", remove_console(Config::All(true)));
+
+    // Passes like `chain!` drive their children from the module items.
+    let items = GLOBALS.set(&Default::default(), || module.body.fold_with(&mut pass));
+
+    assert_eq!(items.len(), 2);
+    let stats = context.pass_stats.take();
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].name, "remove_console");
+    assert!(stats[0].nodes_after < stats[0].nodes_before);
+}
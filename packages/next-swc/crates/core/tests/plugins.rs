@@ -0,0 +1,58 @@
+#![cfg(feature = "plugin")]
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use next_swc::plugins::{plugins, PluginConfig};
+use once_cell::sync::Lazy;
+use swc_core::{
+    common::{chain, FileName, Mark},
+    ecma::parser::{EsConfig, Syntax},
+    ecma::transforms::base::resolver,
+    ecma::transforms::testing::test_fixture,
+};
+use testing::fixture;
+
+/// The plugin of `tests/plugins/unresolved_mark_plugin`, built once for all
+/// of the fixtures. Requires the `wasm32-wasi` target.
+static PLUGIN: Lazy<PathBuf> = Lazy::new(|| {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/plugins/unresolved_mark_plugin");
+    let status = Command::new("cargo")
+        .current_dir(&dir)
+        .args(["build", "--release", "--target=wasm32-wasi"])
+        .stderr(Stdio::inherit())
+        .status()
+        .expect("failed to run cargo");
+    assert!(status.success(), "failed to build the test plugin");
+
+    dir.join("target/wasm32-wasi/release/unresolved_mark_plugin.wasm")
+});
+
+#[fixture("tests/fixture/plugins/**/input.js")]
+fn plugins_fixture(input: PathBuf) {
+    let output = input.parent().unwrap().join("output.js");
+    test_fixture(
+        Syntax::Es(EsConfig::default()),
+        &|tr| {
+            let unresolved_mark = Mark::new();
+            chain!(
+                resolver(unresolved_mark, Mark::new(), false),
+                plugins(
+                    tr.cm.clone(),
+                    FileName::Real(input.clone()),
+                    "development",
+                    vec![PluginConfig {
+                        path: PLUGIN.clone(),
+                        config: Default::default(),
+                    }],
+                    unresolved_mark,
+                )
+            )
+        },
+        &input,
+        &output,
+        Default::default(),
+    );
+}
@@ -0,0 +1,29 @@
+use swc_core::{
+    common::Mark,
+    ecma::{
+        ast::{Ident, Program},
+        visit::{as_folder, FoldWith, VisitMut},
+    },
+    plugin::{plugin_transform, proxies::TransformPluginProgramMetadata},
+};
+
+/// Renames references to the global `__PLUGIN__`, which can only be told apart
+/// from bindings of the same name by the mark swc's resolver used.
+struct RenameGlobal {
+    unresolved_mark: Mark,
+}
+
+impl VisitMut for RenameGlobal {
+    fn visit_mut_ident(&mut self, ident: &mut Ident) {
+        if &*ident.sym == "__PLUGIN__" && ident.span.ctxt.outer() == self.unresolved_mark {
+            ident.sym = "__renamed_by_plugin__".into();
+        }
+    }
+}
+
+#[plugin_transform]
+pub fn process_transform(program: Program, metadata: TransformPluginProgramMetadata) -> Program {
+    program.fold_with(&mut as_folder(RenameGlobal {
+        unresolved_mark: metadata.unresolved_mark,
+    }))
+}
@@ -0,0 +1,235 @@
+use std::{cell::RefCell, rc::Rc};
+
+use next_swc::{
+    metadata::{ModuleMetadata, RouteSegmentConfig},
+    react_server_components::Options,
+};
+use serde_json::json;
+
+use crate::common::transform_server_components;
+
+mod common;
+
+/// Transforms `src` as an `app` page, and returns the metadata and the
+/// messages of the reported errors.
+fn transform(src: &str, is_server: bool) -> (ModuleMetadata, Vec<String>) {
+    let metadata: Rc<RefCell<ModuleMetadata>> = Default::default();
+    let (_, diagnostics) = transform_server_components(
+        "/some-project/app/dashboard/page.js",
+        src,
+        &Default::default(),
+        Options {
+            is_server,
+            ..Default::default()
+        },
+        Some(metadata.clone()),
+    );
+
+    let messages = diagnostics.into_iter().map(|d| d.message).collect();
+    (metadata.take(), messages)
+}
+
+#[test]
+fn should_collect_route_segment_config() {
+    let (metadata, errors) = transform(
+        r#"export const revalidate = 60
+export const dynamic = 'force-static'
+export const dynamicParams = false
+export const fetchCache = 'only-cache'
+export const runtime = 'edge'
+export const preferredRegion = ['iad1', 'sfo1']
+export async function generateStaticParams() {
+  return []
+}
+export default function Page() {}
+"#,
+        true,
+    );
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        metadata.route_segment_config,
+        Some(RouteSegmentConfig {
+            revalidate: Some(json!(60)),
+            dynamic: Some("force-static".into()),
+            dynamic_params: Some(false),
+            fetch_cache: Some("only-cache".into()),
+            runtime: Some("edge".into()),
+            preferred_region: Some(json!(["iad1", "sfo1"])),
+            generate_static_params: true,
+        })
+    );
+    assert_eq!(
+        serde_json::to_value(&metadata.route_segment_config).unwrap()["revalidate"],
+        json!(60)
+    );
+}
+
+#[test]
+fn should_report_invalid_route_segment_config() {
+    let (metadata, errors) = transform(
+        r#"export const revalidate = -1
+export const dynamic = 'static'
+export const runtime = 'deno'
+const generateStaticParams = []
+export { generateStaticParams }
+"#,
+        true,
+    );
+
+    assert_eq!(metadata.route_segment_config, Some(Default::default()));
+    assert_eq!(
+        errors,
+        vec![
+            "Invalid value for the `revalidate` route segment config. Expected `false` or a \
+             number of seconds greater than or equal to 0.",
+            "Invalid value for the `dynamic` route segment config. Expected one of \"auto\", \
+             \"force-dynamic\", \"error\", \"force-static\".",
+            "Invalid value for the `runtime` route segment config. Expected one of \"nodejs\", \
+             \"edge\", \"experimental-edge\".",
+            "`generateStaticParams` has to be a function.",
+        ]
+    );
+}
+
+#[test]
+fn should_leave_out_values_computed_at_runtime() {
+    let (metadata, errors) = transform(
+        r#"const RUNTIME = process.env.RUNTIME
+export const revalidate = 60 * 60
+export const runtime = RUNTIME
+export const preferredRegion = ['iad1', process.env.REGION]
+export const dynamic = 'error'
+"#,
+        true,
+    );
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        metadata.route_segment_config,
+        Some(RouteSegmentConfig {
+            dynamic: Some("error".into()),
+            ..Default::default()
+        })
+    );
+    let json = serde_json::to_value(&metadata.route_segment_config).unwrap();
+    assert_eq!(json["revalidate"], json!(null));
+    assert_eq!(json["runtime"], json!(null));
+}
+
+#[test]
+fn should_resolve_exported_bindings() {
+    let (metadata, errors) = transform(
+        r#"const revalidate = 0
+const cache = 'force-cache'
+let dynamic = 'force-static'
+function generateStaticParams() {
+  return []
+}
+export { revalidate, cache as fetchCache, dynamic, generateStaticParams }
+export default function Page() {}
+"#,
+        true,
+    );
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        metadata.route_segment_config,
+        Some(RouteSegmentConfig {
+            revalidate: Some(json!(0)),
+            fetch_cache: Some("force-cache".into()),
+            generate_static_params: true,
+            ..Default::default()
+        })
+    );
+}
+
+#[test]
+fn should_forbid_route_segment_config_in_client_components() {
+    let (metadata, errors) = transform(
+        r#""use client"
+export const revalidate = 0
+export function generateStaticParams() {}
+export default function Page() {}
+"#,
+        true,
+    );
+
+    assert_eq!(metadata.route_segment_config, None);
+    assert_eq!(
+        errors,
+        vec![
+            "The `revalidate` route segment config can't be exported from a Client Component. \
+             Move it to a layout or page without \"use client\".",
+            "The `generateStaticParams` route segment config can't be exported from a Client \
+             Component. Move it to a layout or page without \"use client\".",
+        ]
+    );
+}
+
+#[test]
+fn should_collect_static_metadata() {
+    let (metadata, errors) = transform(
+        r#"export const metadata = {
+  title: 'Dashboard',
+  keywords: ['next', `react`],
+  robots: { index: false, follow: true },
+  viewport: { width: 1 },
+}
+export default function Page() {}
+"#,
+        true,
+    );
+
+    assert!(errors.is_empty());
+    assert_eq!(
+        metadata.static_metadata,
+        Some(json!({
+            "title": "Dashboard",
+            "keywords": ["next", "react"],
+            "robots": { "index": false, "follow": true },
+            "viewport": { "width": 1 },
+        }))
+    );
+
+    let (metadata, errors) = transform(
+        "export const metadata = { title: process.env.TITLE }\n",
+        true,
+    );
+    assert!(errors.is_empty());
+    assert_eq!(metadata.static_metadata, None);
+}
+
+#[test]
+fn should_report_invalid_metadata_exports() {
+    let (metadata, errors) = transform(
+        r#"export const metadata = { title: 'Dashboard' }
+export async function generateMetadata() {
+  return {}
+}
+"#,
+        true,
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "`metadata` and `generateMetadata` can't be exported from the same layout or page. \
+             Export `generateMetadata` only when the metadata depends on dynamic data."
+        ]
+    );
+    assert_eq!(
+        metadata.static_metadata,
+        Some(json!({ "title": "Dashboard" }))
+    );
+
+    let (_, errors) = transform(
+        r#""use client"
+export const metadata = {}
+"#,
+        false,
+    );
+    assert_eq!(
+        errors,
+        vec!["`metadata` is not allowed in Client Components."]
+    );
+}
@@ -0,0 +1,8 @@
+'use client'
+
+import { getSecret } from './secret'
+import { format } from './utils'
+
+export default function Counter({ label }) {
+  return <button data-secret={getSecret()}>{format(label)}</button>
+}
@@ -0,0 +1,6 @@
+import { format } from './utils'
+import Counter from './counter'
+
+export default function Page() {
+  return <Counter label={format('count')} />
+}
@@ -0,0 +1,5 @@
+import 'server-only'
+
+export function getSecret() {
+  return process.env.SECRET
+}
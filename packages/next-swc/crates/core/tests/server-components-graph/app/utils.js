@@ -0,0 +1,5 @@
+import 'client-only'
+
+export function format(text) {
+  return text.toUpperCase()
+}
@@ -0,0 +1,5 @@
+import 'client-only'
+
+export function useTitle(text) {
+  return text.trim()
+}
@@ -0,0 +1,2 @@
+export { Title } from './title'
+export * from './hooks'
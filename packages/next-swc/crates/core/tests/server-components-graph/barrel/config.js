@@ -0,0 +1,3 @@
+require('client-only')
+
+module.exports = { title: 'Hello' }
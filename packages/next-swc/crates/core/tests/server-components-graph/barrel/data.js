@@ -0,0 +1,5 @@
+const config = require('./config')
+
+export async function load() {
+  return config.title
+}
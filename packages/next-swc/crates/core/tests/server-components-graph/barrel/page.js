@@ -0,0 +1,6 @@
+import { Title } from './components'
+
+export default async function Page() {
+  const { load } = await import('./data')
+  return <Title text={await load()} />
+}
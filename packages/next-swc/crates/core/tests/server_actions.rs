@@ -0,0 +1,197 @@
+use std::{cell::RefCell, rc::Rc};
+
+use next_swc::{
+    metadata::ModuleMetadata, react_server_components::Options, server_actions::action_id,
+};
+
+use crate::common::transform_server_components;
+
+mod common;
+
+const FILENAME: &str = "/some-project/src/actions.js";
+
+/// Returns the metadata and the messages of the reported errors.
+fn transform(src: &str, is_server: bool) -> (ModuleMetadata, Vec<String>) {
+    transform_file(
+        FILENAME,
+        src,
+        Options {
+            is_server,
+            ..Default::default()
+        },
+    )
+}
+
+fn transform_file(filename: &str, src: &str, options: Options) -> (ModuleMetadata, Vec<String>) {
+    let metadata: Rc<RefCell<ModuleMetadata>> = Default::default();
+    let (_, diagnostics) = transform_server_components(
+        filename,
+        src,
+        &Default::default(),
+        options,
+        Some(metadata.clone()),
+    );
+
+    let messages = diagnostics.into_iter().map(|d| d.message).collect();
+    (metadata.take(), messages)
+}
+
+#[test]
+fn should_return_the_same_action_ids_on_both_layers() {
+    let src = r#""use server"
+export async function a() {}
+export default async () => {}
+"#;
+    let (server, errors) = transform(src, true);
+    assert!(errors.is_empty());
+    let (client, errors) = transform(src, false);
+    assert!(errors.is_empty());
+
+    assert_eq!(server.server_actions, client.server_actions);
+    let names: Vec<_> = server.server_actions.iter().map(|a| &*a.name).collect();
+    assert_eq!(names, vec!["a", "default"]);
+    assert_eq!(server.server_actions[0].id, action_id(FILENAME, "a"));
+}
+
+#[test]
+fn should_derive_action_ids_from_the_path_relative_to_the_root() {
+    let src = "\"use server\"\nexport async function a() {}\n";
+    let action_ids = |filename: &str, relative_file_path_from_root: &str| {
+        let (metadata, errors) = transform_file(
+            filename,
+            src,
+            Options {
+                is_server: true,
+                relative_file_path_from_root: Some(relative_file_path_from_root.into()),
+                ..Default::default()
+            },
+        );
+        assert!(errors.is_empty());
+        metadata.server_actions
+    };
+
+    let first = action_ids("/first-checkout/app/actions.js", "app/actions.js");
+    assert_eq!(first[0].id, action_id("app/actions.js", "a"));
+    assert_eq!(
+        first,
+        action_ids("/ci/second-checkout/app/actions.js", "app/actions.js")
+    );
+    assert_eq!(
+        first,
+        action_ids("C:\\projects\\app\\actions.js", "app\\actions.js")
+    );
+}
+
+#[test]
+fn should_only_allow_async_function_exports() {
+    let (metadata, errors) = transform(
+        r#""use server"
+export async function a() {}
+export function b() {}
+export const c = 1
+"#,
+        true,
+    );
+
+    assert_eq!(metadata.server_actions.len(), 1);
+    assert_eq!(
+        errors,
+        vec![
+            "Only async functions can be exported from a \"use server\" file.",
+            "Only async functions can be exported from a \"use server\" file.",
+        ]
+    );
+}
+
+#[test]
+fn should_report_invalid_function_actions() {
+    let (metadata, errors) = transform(
+        r#"function a() {
+  "use server"
+}
+const form = {
+  async submit() {
+    "use server"
+  },
+}
+"#,
+        true,
+    );
+
+    assert!(metadata.server_actions.is_empty());
+    assert_eq!(
+        errors,
+        vec![
+            "Server Actions have to be async functions.",
+            "Methods can't be Server Actions. Declare the Server Action as a function instead.",
+        ]
+    );
+}
+
+#[test]
+fn should_reject_inline_actions_capturing_variables() {
+    let (metadata, errors) = transform(
+        r#"export default function Page({ id }) {
+  const save = async (data) => {
+    "use server"
+    await db.save(id, data)
+  }
+  return { action: save }
+}
+"#,
+        true,
+    );
+
+    assert!(metadata.server_actions.is_empty());
+    assert_eq!(
+        errors,
+        vec![
+            "Inline Server Actions can't use the variables of the functions they are declared in, \
+             like `id`. Pass the values as arguments instead."
+        ]
+    );
+}
+
+#[test]
+fn should_hoist_inline_actions_with_the_same_ids_on_both_layers() {
+    let src = r#"export default function Page() {
+  async function save(id, data) {
+    "use server"
+    await db.save(id, data)
+  }
+  return { save, remove: async (id) => { "use server"; await db.remove(id) } }
+}
+"#;
+    let (server, errors) = transform(src, true);
+    assert!(errors.is_empty());
+    let (client, errors) = transform(src, false);
+    assert!(errors.is_empty());
+
+    assert_eq!(server.server_actions, client.server_actions);
+    let names: Vec<_> = server.server_actions.iter().map(|a| &*a.name).collect();
+    assert_eq!(names, vec!["$$INLINE_ACTION_0", "$$INLINE_ACTION_1"]);
+    assert_eq!(
+        server.server_actions[0].id,
+        action_id(FILENAME, "$$INLINE_ACTION_0")
+    );
+}
+
+#[test]
+fn should_forbid_function_actions_in_client_components() {
+    let (_, errors) = transform(
+        r#""use client"
+export async function a() {
+  "use server"
+}
+"#,
+        false,
+    );
+
+    assert_eq!(
+        errors,
+        vec![
+            "Server Actions can't be declared in Client Components. Move them to a separate file \
+             with \"use server\" at the top, and import them from there."
+        ]
+    );
+}
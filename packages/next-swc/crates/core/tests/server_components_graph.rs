@@ -0,0 +1,131 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use next_swc::server_components_graph::{validate_server_components_graph, GraphOptions};
+
+fn file(name: &str) -> String {
+    fixture_file("app", name)
+}
+
+fn fixture_file(dir: &str, name: &str) -> String {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/server-components-graph")
+        .join(dir)
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn resolved(imports: &[(&str, &str)]) -> HashMap<String, String> {
+    resolved_in("app", imports)
+}
+
+fn resolved_in(dir: &str, imports: &[(&str, &str)]) -> HashMap<String, String> {
+    imports
+        .iter()
+        .map(|(specifier, name)| (specifier.to_string(), fixture_file(dir, name)))
+        .collect()
+}
+
+#[test]
+fn should_report_boundary_violations_with_import_chains() {
+    let options = GraphOptions {
+        entries: vec![file("page.js")],
+        resolved: HashMap::from([
+            (
+                file("page.js"),
+                resolved(&[("./utils", "utils.js"), ("./counter", "counter.js")]),
+            ),
+            (
+                file("counter.js"),
+                resolved(&[("./secret", "secret.js"), ("./utils", "utils.js")]),
+            ),
+        ]),
+        ..Default::default()
+    };
+
+    let violations = validate_server_components_graph(&options);
+    assert_eq!(violations.len(), 2);
+
+    assert_eq!(
+        violations[0].message,
+        "NEXT_RSC_ERR_SERVER_IMPORT: client-only"
+    );
+    assert_eq!(violations[0].chain, vec![file("page.js"), file("utils.js")]);
+
+    assert_eq!(
+        violations[1].message,
+        "NEXT_RSC_ERR_CLIENT_IMPORT: server-only"
+    );
+    assert_eq!(
+        violations[1].chain,
+        vec![file("page.js"), file("counter.js"), file("secret.js")]
+    );
+    assert!(violations[1].to_string().ends_with(&format!(
+        "Import trace:\n  {}\n  {}\n  {}",
+        file("page.js"),
+        file("counter.js"),
+        file("secret.js")
+    )));
+}
+
+#[test]
+fn should_follow_re_exports_and_dynamic_imports() {
+    let file = |name| fixture_file("barrel", name);
+    let options = GraphOptions {
+        entries: vec![file("page.js")],
+        resolved: HashMap::from([
+            (
+                file("page.js"),
+                resolved_in(
+                    "barrel",
+                    &[
+                        ("./components", "components/index.js"),
+                        ("./data", "data.js"),
+                    ],
+                ),
+            ),
+            (
+                file("components/index.js"),
+                resolved_in(
+                    "barrel",
+                    &[
+                        ("./title", "components/title.js"),
+                        ("./hooks", "components/hooks.js"),
+                    ],
+                ),
+            ),
+            (
+                file("data.js"),
+                resolved_in("barrel", &[("./config", "config.js")]),
+            ),
+        ]),
+        ..Default::default()
+    };
+
+    let violations = validate_server_components_graph(&options);
+    assert_eq!(violations.len(), 2);
+
+    // `export * from` in a barrel file.
+    assert_eq!(
+        violations[0].message,
+        "NEXT_RSC_ERR_SERVER_IMPORT: client-only"
+    );
+    assert_eq!(
+        violations[0].chain,
+        vec![
+            file("page.js"),
+            file("components/index.js"),
+            file("components/hooks.js")
+        ]
+    );
+
+    // `import()` of a module which uses `require()`.
+    assert_eq!(
+        violations[1].message,
+        "NEXT_RSC_ERR_SERVER_IMPORT: client-only"
+    );
+    assert_eq!(
+        violations[1].chain,
+        vec![file("page.js"), file("data.js"), file("config.js")]
+    );
+}
@@ -0,0 +1,90 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use next_swc::transform_cache::{cache_key, TransformCache};
+
+/// An empty directory for the test, removed up front so reruns start clean.
+fn cache_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!(
+        "next-swc-transform-cache-{}-{}",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&directory);
+    directory
+}
+
+#[test]
+fn should_hit_and_miss() {
+    let cache = TransformCache::new(10, None).unwrap().unwrap();
+    let key = cache_key(&[b"const a = 1", b"{}"], &[]);
+
+    assert_eq!(cache.get(&key), None::<String>);
+
+    cache.put(key.clone(), &"output".to_string());
+    assert_eq!(cache.get(&key), Some("output".to_string()));
+    assert_eq!(cache.get(&cache_key(&[b"const a = 2", b"{}"], &[])), None);
+}
+
+#[test]
+fn should_be_disabled_without_entries_or_directory() {
+    assert!(TransformCache::<String>::new(0, None).unwrap().is_none());
+}
+
+#[test]
+fn should_key_by_options() {
+    assert_ne!(
+        cache_key(&[b"const a = 1", br#"{"isServer":true}"#], &[]),
+        cache_key(&[b"const a = 1", br#"{"isServer":false}"#], &[])
+    );
+    // Parts are length-prefixed, so moving bytes between them changes the key.
+    assert_ne!(
+        cache_key(&[b"ab", b"c"], &[]),
+        cache_key(&[b"a", b"bc"], &[])
+    );
+}
+
+#[test]
+fn should_key_by_files() {
+    let directory = cache_directory("files");
+    fs::create_dir_all(&directory).unwrap();
+    let plugin = directory.join("plugin.wasm");
+
+    fs::write(&plugin, b"first build").unwrap();
+    let first = cache_key(&[b"const a = 1"], &[plugin.as_path()]);
+    assert_eq!(first, cache_key(&[b"const a = 1"], &[plugin.as_path()]));
+
+    // A plugin rebuilt at the same path.
+    fs::write(&plugin, b"second, larger build").unwrap();
+    assert_ne!(first, cache_key(&[b"const a = 1"], &[plugin.as_path()]));
+
+    assert_ne!(
+        first,
+        cache_key(&[b"const a = 1"], &[Path::new("/missing/plugin.wasm")])
+    );
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn should_persist_entries_to_the_directory() {
+    let directory = cache_directory("disk");
+    let key = cache_key(&[b"const a = 1"], &[]);
+
+    let cache = TransformCache::new(0, Some(directory.clone()))
+        .unwrap()
+        .unwrap();
+    cache.put(key.clone(), &vec!["output".to_string()]);
+    assert!(directory.join(format!("{}.json", key)).exists());
+
+    // A new cache, like the one of another process, reads the entry back.
+    let cache = TransformCache::<Vec<String>>::new(10, Some(directory.clone()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(cache.get(&key), Some(vec!["output".to_string()]));
+    assert_eq!(cache.get(&cache_key(&[b"const a = 2"], &[])), None);
+
+    fs::remove_dir_all(&directory).unwrap();
+}
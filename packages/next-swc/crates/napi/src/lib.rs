@@ -37,17 +37,21 @@ extern crate swc_core;
 use std::{env, panic::set_hook, sync::Arc};
 
 use backtrace::Backtrace;
-use fxhash::FxHashSet;
 use napi::bindgen_prelude::*;
 use swc_core::{
-    base::{Compiler, TransformOutput},
+    base::Compiler,
     common::{sync::Lazy, FilePathMapping, SourceMap},
 };
 
+use crate::transform::TransformResult;
+
+pub mod manifests;
 pub mod mdx;
 pub mod minify;
 pub mod parse;
+pub mod server_components_graph;
 pub mod transform;
+pub mod transform_cache;
 pub mod turbopack;
 pub mod turbotrace;
 pub mod util;
@@ -73,11 +77,16 @@ fn get_compiler() -> Arc<Compiler> {
     COMPILER.clone()
 }
 
-pub fn complete_output(
-    env: &Env,
-    output: TransformOutput,
-    eliminated_packages: FxHashSet<String>,
-) -> napi::Result<Object> {
+pub fn complete_output(env: &Env, result: TransformResult) -> napi::Result<Object> {
+    let TransformResult {
+        output,
+        eliminated_packages,
+        pass_stats,
+        metadata,
+        module_format,
+        diagnostics,
+    } = result;
+
     let mut js_output = env.create_object()?;
     js_output.set_named_property("code", env.create_string_from_std(output.code)?)?;
     if let Some(map) = output.map {
@@ -89,6 +98,27 @@ pub fn complete_output(
             env.create_string_from_std(serde_json::to_string(&eliminated_packages)?)?,
         )?;
     }
+    if !diagnostics.is_empty() {
+        js_output.set_named_property(
+            "diagnostics",
+            env.create_string_from_std(serde_json::to_string(&diagnostics)?)?,
+        )?;
+    }
+    if !pass_stats.is_empty() {
+        js_output.set_named_property(
+            "passStats",
+            env.create_string_from_std(serde_json::to_string(&pass_stats)?)?,
+        )?;
+    }
+    if let Some(metadata) = metadata {
+        js_output.set_named_property(
+            "metadata",
+            env.create_string_from_std(serde_json::to_string(&metadata)?)?,
+        )?;
+    }
+    if let Some(module_format) = module_format {
+        js_output.set_named_property("moduleFormat", env.create_string(module_format.as_str())?)?;
+    }
     Ok(js_output)
 }
 
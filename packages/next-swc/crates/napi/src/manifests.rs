@@ -0,0 +1,22 @@
+use napi::bindgen_prelude::*;
+use next_swc::manifests::{self, ClientReferenceFragment, LoadableFragment};
+
+/// Merges the `clientReference` metadata of the transformed client entries,
+/// given as a JSON array, into the client reference manifest.
+#[napi]
+pub fn merge_client_reference_manifest(fragments: Buffer) -> napi::Result<String> {
+    let fragments: Vec<ClientReferenceFragment> = serde_json::from_slice(fragments.as_ref())?;
+    let manifest = manifests::merge_client_reference_fragments(fragments);
+
+    Ok(serde_json::to_string(&manifest)?)
+}
+
+/// Merges the `loadableModules` metadata of the transformed files, given as a
+/// JSON array, into the loadable manifest.
+#[napi]
+pub fn merge_loadable_manifest(fragments: Buffer) -> napi::Result<String> {
+    let fragments: Vec<LoadableFragment> = serde_json::from_slice(fragments.as_ref())?;
+    let manifest = manifests::merge_loadable_fragments(fragments);
+
+    Ok(serde_json::to_string(&manifest)?)
+}
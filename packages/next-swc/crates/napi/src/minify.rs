@@ -29,6 +29,7 @@ use std::sync::Arc;
 
 use fxhash::FxHashMap;
 use napi::bindgen_prelude::*;
+use next_swc::diagnostics::{with_diagnostics, Diagnostic};
 use serde::Deserialize;
 use swc_core::{
     base::{config::JsMinifyOptions, try_with_handler, BoolOrDataConfig, TransformOutput},
@@ -36,12 +37,16 @@ use swc_core::{
     ecma::minifier::option::terser::TerserCompressorOptions,
 };
 
-use crate::{get_compiler, util::MapErr};
+use crate::{
+    get_compiler,
+    util::{reject_with_diagnostics, with_failure, MapErr},
+};
 
 pub struct MinifyTask {
     c: Arc<swc_core::base::Compiler>,
     code: MinifyTarget,
     opts: swc_core::base::config::JsMinifyOptions,
+    diagnostics: Vec<Diagnostic>,
 }
 
 #[derive(Deserialize)]
@@ -79,7 +84,8 @@ impl Task for MinifyTask {
     type JsValue = TransformOutput;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
-        try_with_handler(
+        let mut diagnostics = vec![];
+        let res = try_with_handler(
             self.c.cm.clone(),
             swc_core::base::HandlerOpts {
                 color: ColorConfig::Never,
@@ -87,30 +93,43 @@ impl Task for MinifyTask {
             },
             |handler| {
                 GLOBALS.set(&Default::default(), || {
-                    let fm = self.code.to_file(self.c.cm.clone());
-
-                    self.c.minify(
-                        fm,
-                        handler,
-                        &JsMinifyOptions {
-                            compress: TerserCompressorOptions {
-                                // inline: TerserInlineOption::Num(0).into(),
-                                ..Default::default()
-                            }
-                            .into(),
-                            mangle: BoolOrDataConfig::from_bool(false),
-                            ..self.opts.clone()
-                        },
-                    )
+                    let (res, collected) =
+                        with_diagnostics(self.c.cm.clone(), handler, |handler| {
+                            let fm = self.code.to_file(self.c.cm.clone());
+
+                            self.c.minify(
+                                fm,
+                                handler,
+                                &JsMinifyOptions {
+                                    compress: TerserCompressorOptions {
+                                        // inline: TerserInlineOption::Num(0).into(),
+                                        ..Default::default()
+                                    }
+                                    .into(),
+                                    mangle: BoolOrDataConfig::from_bool(false),
+                                    ..self.opts.clone()
+                                },
+                            )
+                        });
+                    diagnostics = collected;
+                    res
                 })
             },
-        )
-        .convert_err()
+        );
+
+        if let Err(err) = &res {
+            self.diagnostics = with_failure(diagnostics, err);
+        }
+        res.convert_err()
     }
 
     fn resolve(&mut self, _: napi::Env, output: Self::Output) -> napi::Result<Self::JsValue> {
         Ok(output)
     }
+
+    fn reject(&mut self, env: napi::Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+        Err(reject_with_diagnostics(&env, err, &self.diagnostics))
+    }
 }
 
 #[napi]
@@ -124,13 +143,18 @@ pub fn minify(
 
     let c = get_compiler();
 
-    let task = MinifyTask { c, code, opts };
+    let task = MinifyTask {
+        c,
+        code,
+        opts,
+        diagnostics: vec![],
+    };
 
     Ok(AsyncTask::with_optional_signal(task, signal))
 }
 
 #[napi]
-pub fn minify_sync(input: Buffer, opts: Buffer) -> napi::Result<TransformOutput> {
+pub fn minify_sync(env: Env, input: Buffer, opts: Buffer) -> napi::Result<TransformOutput> {
     let code: MinifyTarget = serde_json::from_slice(&input)?;
     let opts = serde_json::from_slice(&opts)?;
 
@@ -138,13 +162,33 @@ pub fn minify_sync(input: Buffer, opts: Buffer) -> napi::Result<TransformOutput>
 
     let fm = code.to_file(c.cm.clone());
 
-    try_with_handler(
+    let mut diagnostics = vec![];
+    let res = try_with_handler(
         c.cm.clone(),
         swc_core::base::HandlerOpts {
             color: ColorConfig::Never,
             skip_filename: true,
         },
-        |handler| GLOBALS.set(&Default::default(), || c.minify(fm, handler, &opts)),
-    )
-    .convert_err()
+        |handler| {
+            GLOBALS.set(&Default::default(), || {
+                let (res, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                    c.minify(fm, handler, &opts)
+                });
+                diagnostics = collected;
+                res
+            })
+        },
+    );
+
+    match res {
+        Ok(output) => Ok(output),
+        Err(err) => {
+            let diagnostics = with_failure(diagnostics, &err);
+            Err(reject_with_diagnostics(
+                &env,
+                napi::Error::new(Status::GenericFailure, format!("{:?}", err)),
+                &diagnostics,
+            ))
+        }
+    }
 }
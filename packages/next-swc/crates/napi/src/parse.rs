@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use anyhow::Context as _;
 use napi::bindgen_prelude::*;
+use next_swc::diagnostics::{with_diagnostics, Diagnostic};
 use swc_core::{
     base::{config::ParseOptions, try_with_handler},
     common::{
@@ -9,12 +10,14 @@ use swc_core::{
     },
 };
 
-use crate::util::MapErr;
+use crate::util::{reject_with_diagnostics, with_failure, MapErr};
 
 pub struct ParseTask {
     pub filename: FileName,
     pub src: String,
     pub options: Buffer,
+    /// Diagnostics of a failed parse, attached to the rejected error.
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[napi]
@@ -36,6 +39,7 @@ impl Task for ParseTask {
             };
             let fm =
                 c.cm.new_source_file(self.filename.clone(), self.src.clone());
+            let mut diagnostics = vec![];
             let program = try_with_handler(
                 c.cm.clone(),
                 swc_core::base::HandlerOpts {
@@ -43,17 +47,24 @@ impl Task for ParseTask {
                     skip_filename: false,
                 },
                 |handler| {
-                    c.parse_js(
-                        fm,
-                        handler,
-                        options.target,
-                        options.syntax,
-                        options.is_module,
-                        comments,
-                    )
+                    let (res, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                        c.parse_js(
+                            fm,
+                            handler,
+                            options.target,
+                            options.syntax,
+                            options.is_module,
+                            comments,
+                        )
+                    });
+                    diagnostics = collected;
+                    res
                 },
-            )
-            .convert_err()?;
+            );
+            if let Err(err) = &program {
+                self.diagnostics = with_failure(diagnostics, err);
+            }
+            let program = program.convert_err()?;
 
             let ast_json = serde_json::to_string(&program)
                 .context("failed to serialize Program")
@@ -66,6 +77,10 @@ impl Task for ParseTask {
     fn resolve(&mut self, _env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
         Ok(result)
     }
+
+    fn reject(&mut self, env: Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+        Err(reject_with_diagnostics(&env, err, &self.diagnostics))
+    }
 }
 
 #[napi]
@@ -85,6 +100,7 @@ pub fn parse(
             filename,
             src,
             options,
+            diagnostics: vec![],
         },
         signal,
     )
@@ -0,0 +1,33 @@
+use napi::bindgen_prelude::*;
+use next_swc::server_components_graph::{self, GraphOptions};
+
+pub struct ValidateGraphTask {
+    pub options: Buffer,
+}
+
+#[napi]
+impl Task for ValidateGraphTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let options: GraphOptions = serde_json::from_slice(self.options.as_ref())?;
+        let violations = server_components_graph::validate_server_components_graph(&options);
+
+        Ok(serde_json::to_string(&violations)?)
+    }
+
+    fn resolve(&mut self, _env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(result)
+    }
+}
+
+/// Reports the imports crossing the boundary between Server and Client
+/// Components anywhere in the import graph of the entries, as a JSON string.
+#[napi]
+pub fn validate_server_components_graph(
+    options: Buffer,
+    signal: Option<AbortSignal>,
+) -> AsyncTask<ValidateGraphTask> {
+    AsyncTask::with_optional_signal(ValidateGraphTask { options }, signal)
+}
@@ -28,24 +28,40 @@ DEALINGS IN THE SOFTWARE.
 
 use std::fs::read_to_string;
 use std::{
-    cell::RefCell,
+    cell::Cell,
     panic::{catch_unwind, AssertUnwindSafe},
-    rc::Rc,
     sync::Arc,
 };
 
 use anyhow::{anyhow, bail, Context as _};
 use fxhash::FxHashSet;
 use napi::bindgen_prelude::*;
-use next_swc::{custom_before_pass, TransformOptions};
+use next_swc::{
+    custom_before_pass,
+    diagnostics::{with_diagnostics, Diagnostic},
+    metadata::ModuleMetadata,
+    module_format::ModuleFormat,
+    options_schema::{transform_options_schema, OptionError},
+    pass_stats::PassStats,
+    PassContext, TransformOptions,
+};
+use rayon::prelude::*;
+use serde::Deserialize;
 use swc_core::common::comments::SingleThreadedComments;
 use swc_core::{
     base::{try_with_handler, Compiler, TransformOutput},
-    common::{errors::ColorConfig, FileName, GLOBALS},
+    common::{
+        errors::{ColorConfig, Handler},
+        FileName, Mark, GLOBALS,
+    },
     ecma::transforms::base::pass::noop,
 };
 
-use crate::{complete_output, get_compiler, util::MapErr};
+use crate::{
+    complete_output, get_compiler,
+    transform_cache::{get_cached, put_cached, transform_cache, transform_cache_key},
+    util::{reject_with_diagnostics, with_failure},
+};
 
 /// Input to transform
 #[derive(Debug)]
@@ -56,107 +72,229 @@ pub enum Input {
     FromFilename,
 }
 
+/// Output of a successful transform.
+pub struct TransformResult {
+    pub output: TransformOutput,
+    /// Packages whose imports were removed by `next_ssg`.
+    pub eliminated_packages: FxHashSet<String>,
+    /// Empty unless `collectPassStats` is set.
+    pub pass_stats: Vec<PassStats>,
+    /// Only set if `collectMetadata` is set.
+    pub metadata: Option<ModuleMetadata>,
+    pub module_format: Option<ModuleFormat>,
+    /// Non-fatal diagnostics.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Shared state the passes of `custom_before_pass` report into.
+#[derive(Default)]
+struct PassOutputs {
+    context: PassContext,
+    collect_metadata: Cell<bool>,
+}
+
 pub struct TransformTask {
     pub c: Arc<Compiler>,
     pub input: Input,
     pub options: Buffer,
+    /// Diagnostics of a failed transform, attached to the rejected error.
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl TransformTask {
+    pub fn new(c: Arc<Compiler>, input: Input, options: Buffer) -> Self {
+        TransformTask {
+            c,
+            input,
+            options,
+            diagnostics: vec![],
+        }
+    }
 }
 
 impl Task for TransformTask {
-    type Output = (TransformOutput, FxHashSet<String>);
+    type Output = TransformResult;
     type JsValue = Object;
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
-        GLOBALS.set(&Default::default(), || {
-            let eliminated_packages: Rc<RefCell<fxhash::FxHashSet<String>>> = Default::default();
-            let res = catch_unwind(AssertUnwindSafe(|| {
-                try_with_handler(
-                    self.c.cm.clone(),
-                    swc_core::base::HandlerOpts {
-                        color: ColorConfig::Never,
-                        skip_filename: true,
-                    },
-                    |handler| {
-                        self.c.run(|| {
-                            let options: TransformOptions = serde_json::from_slice(&self.options)?;
-                            let fm = match &self.input {
-                                Input::Source { src } => {
-                                    let filename = if options.swc.filename.is_empty() {
-                                        FileName::Anon
-                                    } else {
-                                        FileName::Real(options.swc.filename.clone().into())
-                                    };
-
-                                    self.c.cm.new_source_file(filename, src.to_string())
-                                }
-                                Input::FromFilename => {
-                                    let filename = &options.swc.filename;
-                                    if filename.is_empty() {
-                                        bail!("no filename is provided via options");
-                                    }
-
-                                    self.c.cm.new_source_file(
-                                        FileName::Real(filename.into()),
-                                        read_to_string(filename).with_context(|| {
-                                            format!("Failed to read source code from {}", filename)
-                                        })?,
-                                    )
-                                }
-                            };
-                            let options = options.patch(&fm);
-
-                            let cm = self.c.cm.clone();
-                            let file = fm.clone();
-
-                            let comments = SingleThreadedComments::default();
-                            self.c.process_js_with_custom_pass(
-                                fm,
-                                None,
-                                handler,
-                                &options.swc,
-                                comments.clone(),
-                                |_| {
-                                    custom_before_pass(
-                                        cm,
-                                        file,
-                                        &options,
-                                        comments.clone(),
-                                        eliminated_packages.clone(),
-                                    )
-                                },
-                                |_| noop(),
-                            )
-                        })
-                    },
-                )
-            }))
-            .map_err(|err| {
-                if let Some(s) = err.downcast_ref::<String>() {
-                    anyhow!("failed to process {}", s)
+        let c = &*self.c;
+        let input = &self.input;
+        let json = &self.options;
+        // Parsed once, for both the cache key and the transform.
+        let options = TransformOptions::from_slice(json);
+        let plugins = options.as_ref().ok().map(|options| options.plugins.clone());
+        let cache_key = || match input {
+            Input::Source { src } => Some(transform_cache_key(
+                &[src.as_bytes(), json],
+                plugins.as_deref()?,
+            )),
+            Input::FromFilename => None,
+        };
+        let res = with_cache(cache_key, || {
+            run_transform(c, |handler, outputs| {
+                let options = options.map_err(invalid_options)?;
+                process(c, handler, input, options, outputs)
+            })
+        });
+
+        res.map_err(|(err, diagnostics)| {
+            self.diagnostics = diagnostics;
+            napi::Error::new(Status::GenericFailure, format!("{:?}", err))
+        })
+    }
+
+    fn resolve(&mut self, env: Env, result: Self::Output) -> napi::Result<Self::JsValue> {
+        complete_output(&env, result)
+    }
+
+    fn reject(&mut self, env: Env, err: napi::Error) -> napi::Result<Self::JsValue> {
+        Err(reject_with_diagnostics(&env, err, &self.diagnostics))
+    }
+}
+
+/// Runs a single transform on the current thread. Diagnostics are kept for
+/// both outcomes.
+fn run_transform<F>(
+    c: &Compiler,
+    op: F,
+) -> Result<TransformResult, (anyhow::Error, Vec<Diagnostic>)>
+where
+    F: FnOnce(&Handler, &PassOutputs) -> anyhow::Result<TransformOutput>,
+{
+    GLOBALS.set(&Default::default(), || {
+        let outputs = PassOutputs::default();
+        let mut diagnostics = vec![];
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            try_with_handler(
+                c.cm.clone(),
+                swc_core::base::HandlerOpts {
+                    color: ColorConfig::Never,
+                    skip_filename: true,
+                },
+                |handler| {
+                    c.run(|| {
+                        let (res, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                            op(handler, &outputs)
+                        });
+                        diagnostics = collected;
+                        res
+                    })
+                },
+            )
+        }))
+        .map_err(|err| {
+            if let Some(s) = err.downcast_ref::<String>() {
+                anyhow!("failed to process {}", s)
+            } else {
+                anyhow!("failed to process")
+            }
+        });
+
+        match res {
+            Ok(Ok(output)) => Ok(TransformResult {
+                output,
+                eliminated_packages: outputs.context.eliminated_packages.take(),
+                pass_stats: outputs.context.pass_stats.take(),
+                metadata: if outputs.collect_metadata.get() {
+                    Some(outputs.context.metadata.take())
                 } else {
-                    anyhow!("failed to process")
-                }
-            });
-
-            match res {
-                Ok(res) => res
-                    .map(|o| (o, eliminated_packages.replace(Default::default())))
-                    .convert_err(),
-                Err(err) => Err(napi::Error::new(
-                    Status::GenericFailure,
-                    format!("{:?}", err),
-                )),
+                    None
+                },
+                module_format: outputs.context.module_format.get(),
+                diagnostics,
+            }),
+            Ok(Err(err)) | Err(err) => {
+                let diagnostics = with_failure(diagnostics, &err);
+                Err((err, diagnostics))
             }
-        })
+        }
+    })
+}
+
+/// Returns the cached result for `key` if the transform cache is configured,
+/// otherwise runs `op` and caches its result if it succeeded.
+fn with_cache<K, F>(key: K, op: F) -> Result<TransformResult, (anyhow::Error, Vec<Diagnostic>)>
+where
+    K: FnOnce() -> Option<String>,
+    F: FnOnce() -> Result<TransformResult, (anyhow::Error, Vec<Diagnostic>)>,
+{
+    let cache = transform_cache().and_then(|cache| key().map(|key| (cache, key)));
+    if let Some((cache, key)) = &cache {
+        if let Some(cached) = get_cached(cache, key) {
+            return Ok(cached);
+        }
     }
 
-    fn resolve(
-        &mut self,
-        env: Env,
-        (output, eliminated_packages): Self::Output,
-    ) -> napi::Result<Self::JsValue> {
-        complete_output(&env, output, eliminated_packages)
+    let res = op();
+    if let (Ok(result), Some((cache, key))) = (&res, cache) {
+        put_cached(&cache, key, result);
     }
+    res
+}
+
+fn process(
+    c: &Compiler,
+    handler: &Handler,
+    input: &Input,
+    options: TransformOptions,
+    outputs: &PassOutputs,
+) -> anyhow::Result<TransformOutput> {
+    let fm = match input {
+        Input::Source { src } => {
+            let filename = if options.swc.filename.is_empty() {
+                FileName::Anon
+            } else {
+                FileName::Real(options.swc.filename.clone().into())
+            };
+
+            c.cm.new_source_file(filename, src.to_string())
+        }
+        Input::FromFilename => {
+            let filename = &options.swc.filename;
+            if filename.is_empty() {
+                bail!("no filename is provided via options");
+            }
+
+            c.cm.new_source_file(
+                FileName::Real(filename.into()),
+                read_to_string(filename)
+                    .with_context(|| format!("Failed to read source code from {}", filename))?,
+            )
+        }
+    };
+    let mut options = options.patch(&fm);
+    outputs.collect_metadata.set(options.collect_metadata);
+
+    let unresolved_mark = Mark::new();
+    options.swc.unresolved_mark = Some(unresolved_mark);
+
+    let cm = c.cm.clone();
+    let file = fm.clone();
+
+    let comments = SingleThreadedComments::default();
+    c.process_js_with_custom_pass(
+        fm,
+        None,
+        handler,
+        &options.swc,
+        comments.clone(),
+        |_| {
+            custom_before_pass(
+                cm,
+                file,
+                &options,
+                comments.clone(),
+                outputs.context.clone(),
+                unresolved_mark,
+            )
+        },
+        |_| noop(),
+    )
+}
+
+fn invalid_options(errors: Vec<OptionError>) -> anyhow::Error {
+    let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+    anyhow!(messages.join("\n"))
 }
 
 #[napi]
@@ -176,7 +314,7 @@ pub fn transform(
         Either3::C(_) => Input::FromFilename,
     };
 
-    let task = TransformTask { c, input, options };
+    let task = TransformTask::new(c, input, options);
     Ok(AsyncTask::with_optional_signal(task, signal))
 }
 
@@ -197,10 +335,144 @@ pub fn transform_sync(
         Either3::C(_) => Input::FromFilename,
     };
 
-    let mut task = TransformTask { c, input, options };
-    let output = task.compute()?;
-    task.resolve(env, output)
+    let mut task = TransformTask::new(c, input, options);
+    match task.compute() {
+        Ok(output) => task.resolve(env, output),
+        Err(err) => task.reject(env, err),
+    }
+}
+
+/// Returns the JSON Schema of the options of `transform`, as a JSON string.
+#[napi]
+pub fn get_transform_options_schema() -> napi::Result<String> {
+    Ok(serde_json::to_string(&transform_options_schema())?)
+}
+
+/// An entry of `transformMany`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransformManyItem {
+    filename: String,
+    /// Read from `filename` if omitted.
+    #[serde(default)]
+    src: Option<String>,
+    /// Falls back to the options shared by all entries.
+    #[serde(default)]
+    options: Option<serde_json::Value>,
 }
+
+pub struct TransformManyTask {
+    pub c: Arc<Compiler>,
+    /// JSON array of `{ filename, src?, options? }`.
+    pub items: Buffer,
+    pub options: Option<Buffer>,
+}
+
+impl Task for TransformManyTask {
+    type Output = Vec<Result<TransformResult, (String, Vec<Diagnostic>)>>;
+    type JsValue = Object;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        let items: Vec<TransformManyItem> = serde_json::from_slice(&self.items)?;
+        let shared_options: Option<TransformOptions> = match &self.options {
+            Some(options) => Some(TransformOptions::from_slice(options).map_err(|errors| {
+                napi::Error::new(Status::InvalidArg, invalid_options(errors).to_string())
+            })?),
+            None => None,
+        };
+
+        // Options are resolved upfront so the shared ones are deserialized once.
+        let caching = transform_cache().is_some();
+        let jobs: Vec<(Input, anyhow::Result<TransformOptions>, Option<String>)> = items
+            .into_iter()
+            .map(|item| {
+                let options = match (&item.options, &shared_options) {
+                    (Some(options), _) => {
+                        TransformOptions::from_json(options.clone()).map_err(invalid_options)
+                    }
+                    (None, Some(options)) => Ok(options.clone()),
+                    (None, None) => Err(anyhow!("no options are provided for {}", item.filename)),
+                };
+                let cache_key = match (&item.src, &item.options, &self.options, &options) {
+                    _ if !caching => None,
+                    (Some(src), Some(options), _, Ok(parsed)) => {
+                        serde_json::to_vec(options).ok().map(|options| {
+                            transform_cache_key(
+                                &[src.as_bytes(), &options, item.filename.as_bytes()],
+                                &parsed.plugins,
+                            )
+                        })
+                    }
+                    (Some(src), None, Some(options), Ok(parsed)) => Some(transform_cache_key(
+                        &[src.as_bytes(), options, item.filename.as_bytes()],
+                        &parsed.plugins,
+                    )),
+                    _ => None,
+                };
+                let options = options.map(|mut options: TransformOptions| {
+                    options.swc.filename = item.filename;
+                    options
+                });
+                let input = match item.src {
+                    Some(src) => Input::Source { src },
+                    None => Input::FromFilename,
+                };
+                (input, options, cache_key)
+            })
+            .collect();
+
+        let c = &*self.c;
+        Ok(jobs
+            .into_par_iter()
+            .map(|(input, options, cache_key)| {
+                with_cache(
+                    || cache_key,
+                    || {
+                        run_transform(c, |handler, outputs| {
+                            process(c, handler, &input, options?, outputs)
+                        })
+                    },
+                )
+                .map_err(|(err, diagnostics)| (format!("{:?}", err), diagnostics))
+            })
+            .collect())
+    }
+
+    fn resolve(&mut self, env: Env, results: Self::Output) -> napi::Result<Self::JsValue> {
+        let mut js_results = env.create_array_with_length(results.len())?;
+        for (i, result) in results.into_iter().enumerate() {
+            let js_result = match result {
+                Ok(result) => complete_output(&env, result)?,
+                Err((error, diagnostics)) => {
+                    let mut js_error = env.create_object()?;
+                    js_error.set_named_property("error", env.create_string_from_std(error)?)?;
+                    js_error.set_named_property(
+                        "diagnostics",
+                        env.create_string_from_std(serde_json::to_string(&diagnostics)?)?,
+                    )?;
+                    js_error
+                }
+            };
+            js_results.set_element(i as u32, js_result)?;
+        }
+        Ok(js_results)
+    }
+}
+
+/// Transforms many files in parallel in one call. Each result is either the
+/// same object `transform` resolves with, or `{ error, diagnostics }`.
+#[napi]
+pub fn transform_many(
+    items: Buffer,
+    options: Option<Buffer>,
+    signal: Option<AbortSignal>,
+) -> napi::Result<AsyncTask<TransformManyTask>> {
+    let c = get_compiler();
+
+    let task = TransformManyTask { c, items, options };
+    Ok(AsyncTask::with_optional_signal(task, signal))
+}
+
 #[test]
 fn test_deser() {
     const JSON_STR: &str = r#"{"jsc":{"parser":{"syntax":"ecmascript","dynamicImport":true,"jsx":true},"transform":{"react":{"runtime":"automatic","pragma":"React.createElement","pragmaFrag":"React.Fragment","throwIfNamespace":true,"development":false,"useBuiltins":true}},"target":"es5"},"filename":"/Users/timneutkens/projects/next.js/packages/next/dist/client/next.js","sourceMaps":false,"sourceFileName":"/Users/timneutkens/projects/next.js/packages/next/dist/client/next.js"}"#;
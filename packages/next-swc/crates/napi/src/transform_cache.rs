@@ -0,0 +1,96 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use fxhash::FxHashSet;
+use next_swc::{
+    diagnostics::Diagnostic,
+    metadata::ModuleMetadata,
+    module_format::ModuleFormat,
+    plugins::PluginConfig,
+    transform_cache::{cache_key, TransformCache},
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use swc_core::base::TransformOutput;
+
+use crate::{transform::TransformResult, util::PACKAGE_VERSION};
+
+static TRANSFORM_CACHE: Lazy<Mutex<Option<Arc<TransformCache<CachedOutput>>>>> =
+    Lazy::new(Default::default);
+
+/// What a cache hit returns instead of running `custom_before_pass`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedOutput {
+    code: String,
+    map: Option<String>,
+    eliminated_packages: FxHashSet<String>,
+    metadata: Option<ModuleMetadata>,
+    module_format: Option<ModuleFormat>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// Keys a transform by the given parts (the source and the options as they
+/// were passed from JS). The version of next-swc and the wasm plugins of the
+/// options are covered too, so changes to any of them never hit a stale
+/// entry.
+pub fn transform_cache_key(parts: &[&[u8]], plugins: &[PluginConfig]) -> String {
+    let parts: Vec<&[u8]> = std::iter::once(PACKAGE_VERSION.as_bytes())
+        .chain(parts.iter().copied())
+        .collect();
+    let plugins: Vec<&Path> = plugins.iter().map(|plugin| plugin.path.as_path()).collect();
+    cache_key(&parts, &plugins)
+}
+
+pub fn get_cached(cache: &TransformCache<CachedOutput>, key: &str) -> Option<TransformResult> {
+    cache.get(key).map(CachedOutput::into_result)
+}
+
+pub fn put_cached(cache: &TransformCache<CachedOutput>, key: String, result: &TransformResult) {
+    cache.put(
+        key,
+        &CachedOutput {
+            code: result.output.code.clone(),
+            map: result.output.map.clone(),
+            eliminated_packages: result.eliminated_packages.clone(),
+            metadata: result.metadata.clone(),
+            module_format: result.module_format,
+            diagnostics: result.diagnostics.clone(),
+        },
+    );
+}
+
+impl CachedOutput {
+    /// Pass stats are not cached, as no pass ran for a cache hit.
+    fn into_result(self) -> TransformResult {
+        TransformResult {
+            output: TransformOutput {
+                code: self.code,
+                map: self.map,
+            },
+            eliminated_packages: self.eliminated_packages,
+            pass_stats: vec![],
+            metadata: self.metadata,
+            module_format: self.module_format,
+            diagnostics: self.diagnostics,
+        }
+    }
+}
+
+/// Returns the transform cache, if one has been configured.
+pub fn transform_cache() -> Option<Arc<TransformCache<CachedOutput>>> {
+    TRANSFORM_CACHE.lock().unwrap().clone()
+}
+
+/// Enables caching of `transform` results keyed by their source and options.
+/// `maxEntries` bounds the in-memory cache, and `directory` additionally
+/// persists entries across processes. Passing neither disables the cache.
+#[napi]
+pub fn configure_transform_cache(max_entries: u32, directory: Option<String>) -> napi::Result<()> {
+    let cache = TransformCache::new(max_entries as usize, directory.map(PathBuf::from))?;
+    *TRANSFORM_CACHE.lock().unwrap() = cache.map(Arc::new);
+
+    Ok(())
+}
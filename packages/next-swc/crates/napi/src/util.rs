@@ -29,7 +29,8 @@ DEALINGS IN THE SOFTWARE.
 use std::{cell::RefCell, env, path::PathBuf};
 
 use anyhow::anyhow;
-use napi::bindgen_prelude::{External, Status};
+use napi::bindgen_prelude::{Env, External, Status};
+use next_swc::diagnostics::{has_errors, Diagnostic};
 #[cfg(feature = "crash-report")]
 use sentry::{init, types::Dsn, ClientInitGuard, ClientOptions};
 use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
@@ -37,7 +38,7 @@ use tracing_subscriber::{filter, prelude::*, util::SubscriberInitExt, Layer};
 
 static TARGET_TRIPLE: &str = include_str!(concat!(env!("OUT_DIR"), "/triple.txt"));
 #[allow(unused)]
-static PACKAGE_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/package.txt"));
+pub(crate) static PACKAGE_VERSION: &str = include_str!(concat!(env!("OUT_DIR"), "/package.txt"));
 
 #[napi]
 pub fn get_target_triple() -> String {
@@ -53,6 +54,32 @@ pub trait MapErr<T>: Into<Result<T, anyhow::Error>> {
 
 impl<T> MapErr<T> for Result<T, anyhow::Error> {}
 
+/// Makes sure a failed task reports at least one error diagnostic, even if the
+/// failure did not go through `HANDLER` (e.g. invalid options).
+pub fn with_failure(mut diagnostics: Vec<Diagnostic>, err: &anyhow::Error) -> Vec<Diagnostic> {
+    if !has_errors(&diagnostics) {
+        diagnostics.push(Diagnostic::from_error_message(format!("{:?}", err)));
+    }
+    diagnostics
+}
+
+/// Creates the JS error for a rejected task. The message is unchanged, and the
+/// collected diagnostics are attached as a JSON string under `diagnostics`.
+pub fn reject_with_diagnostics(
+    env: &Env,
+    err: napi::Error,
+    diagnostics: &[Diagnostic],
+) -> napi::Error {
+    let fallback = napi::Error::new(err.status, err.reason.clone());
+    let attach = || -> napi::Result<napi::Error> {
+        let diagnostics = serde_json::to_string(diagnostics)?;
+        let mut js_err = env.create_error(err)?;
+        js_err.set_named_property("diagnostics", env.create_string_from_std(diagnostics)?)?;
+        Ok(js_err.into_unknown().into())
+    };
+    attach().unwrap_or(fallback)
+}
+
 /// Initialize tracing subscriber to emit traces. This configures subscribers
 /// for Trace Event Format (https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU/preview).
 #[napi]
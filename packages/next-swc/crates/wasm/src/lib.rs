@@ -1,6 +1,11 @@
 use anyhow::{Context, Error};
 use js_sys::JsString;
-use next_swc::{custom_before_pass, TransformOptions};
+use next_swc::{
+    custom_before_pass,
+    diagnostics::{has_errors, with_diagnostics, Diagnostic},
+    options_schema, PassContext, TransformOptions,
+};
+use serde::Serialize;
 use std::sync::Arc;
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::future_to_promise;
@@ -10,15 +15,26 @@ use swc_core::{
     common::{
         comments::{Comments, SingleThreadedComments},
         errors::ColorConfig,
-        FileName, FilePathMapping, SourceMap, GLOBALS,
+        FileName, FilePathMapping, Mark, SourceMap, GLOBALS,
     },
     ecma::transforms::base::pass::noop,
 };
 
 pub mod mdx;
 
-fn convert_err(err: Error) -> JsValue {
-    format!("{:?}", err).into()
+/// Converts a failure into an `Error` which carries the collected diagnostics
+/// under `diagnostics`.
+fn convert_err(err: Error, mut diagnostics: Vec<Diagnostic>) -> JsValue {
+    let message = format!("{:?}", err);
+    if !has_errors(&diagnostics) {
+        diagnostics.push(Diagnostic::from_error_message(message.clone()));
+    }
+
+    let js_err = js_sys::Error::new(&message);
+    if let Ok(diagnostics) = serde_wasm_bindgen::to_value(&diagnostics) {
+        let _ = js_sys::Reflect::set(&js_err, &"diagnostics".into(), &diagnostics);
+    }
+    js_err.into()
 }
 
 #[wasm_bindgen(js_name = "minifySync")]
@@ -29,6 +45,7 @@ pub fn minify_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
 
     let opts: JsMinifyOptions = serde_wasm_bindgen::from_value(opts)?;
 
+    let mut diagnostics = vec![];
     let value = try_with_handler(
         c.cm.clone(),
         swc_core::base::HandlerOpts {
@@ -37,16 +54,20 @@ pub fn minify_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
         },
         |handler| {
             GLOBALS.set(&Default::default(), || {
-                let fm = c.cm.new_source_file(FileName::Anon, s.into());
-                let program = c
-                    .minify(fm, handler, &opts)
-                    .context("failed to minify file")?;
+                let (res, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                    let fm = c.cm.new_source_file(FileName::Anon, s.into());
+                    let program = c
+                        .minify(fm, handler, &opts)
+                        .context("failed to minify file")?;
 
-                Ok(program)
+                    Ok(program)
+                });
+                diagnostics = collected;
+                res
             })
         },
     )
-    .map_err(convert_err)?;
+    .map_err(|err| convert_err(err, diagnostics))?;
 
     Ok(serde_wasm_bindgen::to_value(&value)?)
 }
@@ -63,9 +84,24 @@ pub fn transform_sync(s: JsValue, opts: JsValue) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
 
     let c = compiler();
-    let opts: TransformOptions = serde_wasm_bindgen::from_value(opts)?;
+    let opts =
+        TransformOptions::from_json(serde_wasm_bindgen::from_value(opts)?).map_err(|errors| {
+            let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+            js_sys::Error::new(&messages.join("\n"))
+        })?;
+    // Plugins are loaded from the file system, which the wasm bindings can't
+    // access.
+    if !opts.plugins.is_empty() {
+        return Err(js_sys::Error::new(
+            "The `plugins` option is not supported by the wasm bindings of next-swc. Use the \
+             native bindings to run transform plugins.",
+        )
+        .into());
+    }
 
     let s = s.dyn_into::<js_sys::JsString>();
+    let mut diagnostics = vec![];
+    let context = PassContext::default();
     let out = try_with_handler(
         c.cm.clone(),
         swc_core::base::HandlerOpts {
@@ -74,52 +110,92 @@ pub fn transform_sync(s: JsValue, opts: JsValue) -> Result<JsValue, JsValue> {
         },
         |handler| {
             GLOBALS.set(&Default::default(), || {
-                let out = match s {
-                    Ok(s) => {
-                        let fm = c.cm.new_source_file(
-                            if opts.swc.filename.is_empty() {
-                                FileName::Anon
-                            } else {
-                                FileName::Real(opts.swc.filename.clone().into())
-                            },
-                            s.into(),
-                        );
-                        let cm = c.cm.clone();
-                        let file = fm.clone();
-                        let comments = SingleThreadedComments::default();
-                        c.process_js_with_custom_pass(
-                            fm,
-                            None,
+                let (res, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                    let out = match s {
+                        Ok(s) => {
+                            let fm = c.cm.new_source_file(
+                                if opts.swc.filename.is_empty() {
+                                    FileName::Anon
+                                } else {
+                                    FileName::Real(opts.swc.filename.clone().into())
+                                },
+                                s.into(),
+                            );
+                            let cm = c.cm.clone();
+                            let file = fm.clone();
+                            let comments = SingleThreadedComments::default();
+                            let unresolved_mark = Mark::new();
+                            let swc_opts = swc_core::base::config::Options {
+                                unresolved_mark: Some(unresolved_mark),
+                                ..opts.swc.clone()
+                            };
+                            c.process_js_with_custom_pass(
+                                fm,
+                                None,
+                                handler,
+                                &swc_opts,
+                                comments.clone(),
+                                |_| {
+                                    custom_before_pass(
+                                        cm,
+                                        file,
+                                        &opts,
+                                        comments.clone(),
+                                        context.clone(),
+                                        unresolved_mark,
+                                    )
+                                },
+                                |_| noop(),
+                            )
+                            .context("failed to process js file")?
+                        }
+                        Err(v) => c.process_js(
                             handler,
+                            serde_wasm_bindgen::from_value(v).expect(""),
                             &opts.swc,
-                            comments.clone(),
-                            |_| {
-                                custom_before_pass(
-                                    cm,
-                                    file,
-                                    &opts,
-                                    comments.clone(),
-                                    Default::default(),
-                                )
-                            },
-                            |_| noop(),
-                        )
-                        .context("failed to process js file")?
-                    }
-                    Err(v) => c.process_js(
-                        handler,
-                        serde_wasm_bindgen::from_value(v).expect(""),
-                        &opts.swc,
-                    )?,
-                };
-
-                Ok(out)
+                        )?,
+                    };
+
+                    Ok(out)
+                });
+                diagnostics = collected;
+                res
             })
         },
     )
-    .map_err(convert_err)?;
+    .map_err(|err| convert_err(err, diagnostics.clone()))?;
 
-    Ok(serde_wasm_bindgen::to_value(&out)?)
+    let out = serde_wasm_bindgen::to_value(&out)?;
+    if !diagnostics.is_empty() {
+        js_sys::Reflect::set(
+            &out,
+            &"diagnostics".into(),
+            &serde_wasm_bindgen::to_value(&diagnostics)?,
+        )?;
+    }
+    if let Some(module_format) = context.module_format.get() {
+        js_sys::Reflect::set(&out, &"moduleFormat".into(), &module_format.as_str().into())?;
+    }
+    if opts.collect_pass_stats {
+        js_sys::Reflect::set(
+            &out,
+            &"passStats".into(),
+            &serde_wasm_bindgen::to_value(&*context.pass_stats.borrow())?,
+        )?;
+    }
+    if opts.collect_metadata {
+        js_sys::Reflect::set(
+            &out,
+            &"metadata".into(),
+            // Font loader arguments are arbitrary JSON, which has to become plain
+            // objects rather than `Map`s.
+            &context
+                .metadata
+                .borrow()
+                .serialize(&serde_wasm_bindgen::Serializer::json_compatible())?,
+        )?;
+    }
+    Ok(out)
 }
 
 #[wasm_bindgen(js_name = "transform")]
@@ -129,6 +205,13 @@ pub fn transform(s: JsValue, opts: JsValue) -> js_sys::Promise {
     future_to_promise(async { transform_sync(s, opts) })
 }
 
+/// Returns the JSON Schema of the options of `transform`.
+#[wasm_bindgen(js_name = "transformOptionsSchema")]
+pub fn transform_options_schema() -> Result<JsValue, JsValue> {
+    Ok(options_schema::transform_options_schema()
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
 #[wasm_bindgen(js_name = "parseSync")]
 pub fn parse_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
     console_error_panic_hook::set_once();
@@ -136,6 +219,7 @@ pub fn parse_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
     let c = swc_core::base::Compiler::new(Arc::new(SourceMap::new(FilePathMapping::empty())));
     let opts: ParseOptions = serde_wasm_bindgen::from_value(opts)?;
 
+    let mut diagnostics = vec![];
     try_with_handler(
         c.cm.clone(),
         swc_core::base::HandlerOpts {
@@ -153,8 +237,8 @@ pub fn parse_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
                         None
                     };
 
-                    let program = c
-                        .parse_js(
+                    let (program, collected) = with_diagnostics(c.cm.clone(), handler, |handler| {
+                        c.parse_js(
                             fm,
                             handler,
                             opts.target,
@@ -162,7 +246,9 @@ pub fn parse_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
                             opts.is_module,
                             comments,
                         )
-                        .context("failed to parse code")?;
+                    });
+                    diagnostics = collected;
+                    let program = program.context("failed to parse code")?;
 
                     let s = serde_json::to_string(&program).unwrap();
                     Ok(JsValue::from_str(&s))
@@ -170,7 +256,7 @@ pub fn parse_sync(s: JsString, opts: JsValue) -> Result<JsValue, JsValue> {
             })
         },
     )
-    .map_err(convert_err)
+    .map_err(|err| convert_err(err, diagnostics))
 }
 
 #[wasm_bindgen(js_name = "parse")]
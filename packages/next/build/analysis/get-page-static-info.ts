@@ -36,10 +36,12 @@ export interface PageStaticInfo {
   middleware?: Partial<MiddlewareConfig>
 }
 
-const CLIENT_MODULE_LABEL = `/* __next_internal_client_entry_do_not_use__ */`
+// The label may be followed by the exports of the client entry as JSON.
+const CLIENT_MODULE_LABEL =
+  /\/\* __next_internal_client_entry_do_not_use__ (?:\[.*?\] )?\*\//
 export type RSCModuleType = 'server' | 'client'
 export function getRSCModuleType(source: string): RSCModuleType {
-  return source.includes(CLIENT_MODULE_LABEL)
+  return CLIENT_MODULE_LABEL.test(source)
     ? RSC_MODULE_TYPES.client
     : RSC_MODULE_TYPES.server
 }
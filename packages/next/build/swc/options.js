@@ -255,6 +255,12 @@ export function getLoaderSWCOptions({
     }
   }
 
+  if (baseOptions.serverComponents && relativeFilePathFromRoot) {
+    // Server Action IDs are derived from the path of the module.
+    baseOptions.serverComponents.relativeFilePathFromRoot =
+      relativeFilePathFromRoot
+  }
+
   const isNextDist = nextDistPath.test(filename)
 
   if (isServer) {
@@ -13,6 +13,8 @@ import {
   SERVER_RUNTIME,
   WEBPACK_LAYERS,
   RSC_MOD_REF_PROXY_ALIAS,
+  RSC_ACTION_REGISTRY_ALIAS,
+  RSC_ACTION_PROXY_ALIAS,
 } from '../lib/constants'
 import { EXTERNAL_PACKAGES } from '../lib/server-external-packages'
 import { fileExists } from '../lib/file-exists'
@@ -944,6 +946,10 @@ export default async function getBaseWebpackConfig(
 
       [RSC_MOD_REF_PROXY_ALIAS]:
         'next/dist/build/webpack/loaders/next-flight-loader/module-proxy',
+      [RSC_ACTION_REGISTRY_ALIAS]:
+        'next/dist/build/webpack/loaders/next-flight-loader/action-registry',
+      [RSC_ACTION_PROXY_ALIAS]:
+        'next/dist/build/webpack/loaders/next-flight-loader/action-proxy',
 
       ...(isClient || isEdgeServer
         ? {
@@ -1118,7 +1124,7 @@ export default async function getBaseWebpackConfig(
       }
 
       const notExternalModules =
-        /^(?:private-next-pages\/|next\/(?:dist\/pages\/|(?:app|document|link|image|legacy\/image|constants|dynamic|script|navigation|headers)$)|string-hash|private-next-rsc-mod-ref-proxy$)/
+        /^(?:private-next-pages\/|next\/(?:dist\/pages\/|(?:app|document|link|image|legacy\/image|constants|dynamic|script|navigation|headers)$)|string-hash|private-next-rsc-(?:mod-ref-proxy|action-registry|action-proxy)$)/
       if (notExternalModules.test(request)) {
         return
       }
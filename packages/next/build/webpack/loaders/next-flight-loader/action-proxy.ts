@@ -0,0 +1,23 @@
+import { NEXT_ACTION } from '../../../../client/components/app-router-headers'
+
+// Stands in for a Server Action on the client, calling it on the server by
+// its ID. The arguments and the result have to be serializable as JSON. The
+// request goes to the current page, which runs the action in `app-render`.
+export function createActionProxy(id: string) {
+  return async function action(...args: any[]) {
+    const res = await fetch('', {
+      method: 'POST',
+      headers: {
+        Accept: 'application/json',
+        'Content-Type': 'application/json',
+        [NEXT_ACTION]: id,
+      },
+      body: JSON.stringify(args),
+    })
+
+    if (!res.ok) {
+      throw new Error(`Server Action ${id} failed with status ${res.status}`)
+    }
+    return res.json()
+  }
+}
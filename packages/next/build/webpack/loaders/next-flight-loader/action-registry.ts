@@ -0,0 +1,22 @@
+// Server Actions registered by the modules of the server graph, looked up by
+// their ID when a client calls them. Kept on `globalThis` so that every
+// bundle of the server shares the same registry.
+const REGISTRY_KEY = Symbol.for('next.server.actions')
+
+type ServerAction = (...args: any[]) => Promise<any>
+
+function getRegistry(): Map<string, ServerAction> {
+  const globalObject = globalThis as any
+  if (!globalObject[REGISTRY_KEY]) {
+    globalObject[REGISTRY_KEY] = new Map()
+  }
+  return globalObject[REGISTRY_KEY]
+}
+
+export function registerServerAction(id: string, action: ServerAction) {
+  getRegistry().set(id, action)
+}
+
+export function getServerAction(id: string): ServerAction | undefined {
+  return getRegistry().get(id)
+}
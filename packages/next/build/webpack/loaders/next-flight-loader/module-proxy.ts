@@ -91,12 +91,29 @@ const proxyHandlers: ProxyHandler<object> = {
   },
 }
 
-export function createProxy(moduleId: string) {
+export function createProxy(moduleId: string, async = false) {
   const moduleReference = {
     $$typeof: MODULE_REFERENCE,
     filepath: moduleId,
     name: '*', // Represents the whole object instead of a particular import.
-    async: false,
+    async,
+  }
+  return new Proxy(moduleReference, proxyHandlers)
+}
+
+// A reference to a single export of a client module, which can be used where
+// the names of the exports are known in advance. `async` is set for modules
+// with top-level `await`.
+export function createReference(
+  moduleId: string,
+  name: string,
+  async = false
+) {
+  const moduleReference = {
+    $$typeof: MODULE_REFERENCE,
+    filepath: moduleId,
+    name,
+    async,
   }
   return new Proxy(moduleReference, proxyHandlers)
 }
@@ -15,6 +15,11 @@ export function formatRSCErrorMessage(
     const NEXT_RSC_ERR_REACT_API = /.+NEXT_RSC_ERR_REACT_API: (.*?)\n/s
     const NEXT_RSC_ERR_SERVER_IMPORT = /.+NEXT_RSC_ERR_SERVER_IMPORT: (.*?)\n/s
     const NEXT_RSC_ERR_CLIENT_IMPORT = /.+NEXT_RSC_ERR_CLIENT_IMPORT: (.*?)\n/s
+    // Configured with a custom message in the deny-lists of the transform.
+    // The module comes first, then the message, which can span multiple
+    // lines up to the code frame of the diagnostic.
+    const NEXT_RSC_ERR_CUSTOM =
+      /.+NEXT_RSC_ERR_CUSTOM: (.*?)\n(.*?)(?=\n\s*,-\[|$)/s
 
     if (NEXT_RSC_ERR_REACT_API.test(message)) {
       formattedMessage = message.replace(
@@ -49,6 +54,17 @@ export function formatRSCErrorMessage(
       )
       formattedVerboseMessage =
         '\n\nOne of these is marked as a client entry with "use client":\n'
+    } else if (NEXT_RSC_ERR_CUSTOM.test(message)) {
+      formattedMessage = message.replace(
+        NEXT_RSC_ERR_CUSTOM,
+        (_, source: string, customMessage: string) => {
+          // The lines of the message are prefixed by the gutter of the
+          // diagnostic.
+          const lines = customMessage.replace(/^\s*\| ?/gm, '')
+          return `\n\n${lines}\n\nThis was reported for "${source}".\n\n`
+        }
+      )
+      formattedVerboseMessage = '\n\nImport trace:\n'
     }
 
     return [formattedMessage, formattedVerboseMessage]
@@ -39,10 +39,12 @@ import { HeadManagerContext } from '../shared/lib/head-manager-context'
 import { Writable } from 'stream'
 import stringHash from 'next/dist/compiled/string-hash'
 import {
+  NEXT_ACTION,
   NEXT_ROUTER_PREFETCH,
   NEXT_ROUTER_STATE_TREE,
   RSC,
 } from '../client/components/app-router-headers'
+import { getServerAction } from '../build/webpack/loaders/next-flight-loader/action-registry'
 
 const isEdgeRuntime = process.env.NEXT_RUNTIME === 'edge'
 
@@ -705,6 +707,34 @@ function headersWithoutFlight(headers: IncomingHttpHeaders) {
   return newHeaders
 }
 
+/**
+ * Server Actions are registered when the modules declaring them are
+ * evaluated, so every module of the tree is loaded before looking one up.
+ */
+async function loadLoaderTree(loaderTree: LoaderTree): Promise<void> {
+  const [, parallelRoutes, components] = loaderTree
+  await Promise.all([
+    ...Object.values(components).map((component) => component?.[0]()),
+    ...Object.values(parallelRoutes).map(loadLoaderTree),
+  ])
+}
+
+/**
+ * Reads the arguments of a Server Action, sent as JSON by `createActionProxy`.
+ * The web runtime passes the body of the request as a stream.
+ */
+async function readServerActionArgs(req: IncomingMessage): Promise<any[]> {
+  if (isEdgeRuntime) {
+    return JSON.parse(await new Response((req as any).body).text())
+  }
+
+  let body = ''
+  for await (const chunk of req) {
+    body += chunk
+  }
+  return JSON.parse(body)
+}
+
 async function renderToString(element: React.ReactElement) {
   const renderStream = await ReactDOMServer.renderToReadableStream(element)
   await renderStream.allReady
@@ -815,6 +845,22 @@ export async function renderToHTMLOrFlight(
 
     stripInternalQueries(query)
 
+    const actionId = req.headers[NEXT_ACTION.toLowerCase()]
+    if (req.method === 'POST' && typeof actionId === 'string') {
+      await loadLoaderTree(loaderTree)
+      const action = getServerAction(actionId)
+      if (!action) {
+        throw new Error(
+          `Server Action "${actionId}" was not found. It has to be used by a Server Component of the page calling it.`
+        )
+      }
+
+      const result = await action(...(await readServerActionArgs(req)))
+      return new RenderResult(JSON.stringify(result ?? null), {
+        contentType: 'application/json',
+      })
+    }
+
     const LayoutRouter =
       ComponentMod.LayoutRouter as typeof import('../client/components/layout-router').default
     const RenderFromTemplateContext =
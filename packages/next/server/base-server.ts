@@ -74,7 +74,11 @@ import { getHostname } from '../shared/lib/get-hostname'
 import { parseUrl as parseUrlUtil } from '../shared/lib/router/utils/parse-url'
 import { getNextPathnameInfo } from '../shared/lib/router/utils/get-next-pathname-info'
 import { MiddlewareMatcher } from '../build/analysis/get-page-static-info'
-import { RSC, RSC_VARY_HEADER } from '../client/components/app-router-headers'
+import {
+  NEXT_ACTION,
+  RSC,
+  RSC_VARY_HEADER,
+} from '../client/components/app-router-headers'
 import { FLIGHT_PARAMETERS } from './app-render'
 
 export type FindComponentsResult = {
@@ -1045,6 +1049,16 @@ export default abstract class Server<ServerOptions extends Options = Options> {
       }
     }
 
+    // Server Actions are called with a POST request to the page using them,
+    // which is rendered dynamically to run the action instead.
+    if (
+      isAppPath &&
+      req.method === 'POST' &&
+      typeof req.headers[NEXT_ACTION.toLowerCase()] === 'string'
+    ) {
+      isSSG = false
+    }
+
     // Toggle whether or not this is a Data request
     let isDataReq =
       !!(
@@ -368,9 +368,11 @@ export default class NextWebServer extends BaseServer<WebServerOptions> {
     if (curRenderToHTML) {
       return await curRenderToHTML(
         {
+          method: req.method,
           url: req.url,
           cookies: req.cookies,
           headers: req.headers,
+          body: req.body,
         } as any,
         {} as any,
         pathname,